@@ -0,0 +1,116 @@
+use crate::{ByMonoid, Flip, Monoid, Prod, StackAggregation};
+
+/// 2 つの `StackAggregation` で deque を表し、両端への追加と削除、全体の積をならし O(1) で処理する
+/// `prod` は結合的であればよく、可換である必要はない
+pub struct DequeAggregation<T, F> {
+    /// 先頭が末尾にある
+    front: StackAggregation<T, Flip<F>>,
+    back: StackAggregation<T, F>,
+}
+
+/// `Monoid` によって定まる `DequeAggregation`
+pub type MonoidDequeAggregation<M> = DequeAggregation<<M as Monoid>::Item, ByMonoid<M>>;
+
+impl<T, F: Fn(&T, &T) -> T + Clone> DequeAggregation<T, F> {
+    /// `prod` は 2 つの stack で共有するので `Clone` である必要がある
+    pub fn new<I: Fn() -> T>(id: I, prod: F) -> Self {
+        Self::with_prod(id, prod)
+    }
+}
+
+impl<M: Monoid> MonoidDequeAggregation<M> {
+    pub fn new_monoid() -> Self {
+        Self::with_prod(M::id, ByMonoid::default())
+    }
+}
+
+impl<T, F: Prod<T>> DequeAggregation<T, F> {
+    fn with_prod<I: Fn() -> T>(id: I, prod: F) -> Self
+    where
+        F: Clone,
+    {
+        Self {
+            front: StackAggregation::with_prod(id(), Flip(prod.clone())),
+            back: StackAggregation::with_prod(id(), prod),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// 空なら `id`
+    pub fn prod_all(&self) -> T {
+        self.back
+            .prod
+            .prod(self.front.prod_all(), self.back.prod_all())
+    }
+    pub fn push_front(&mut self, val: T) {
+        self.front.push(val);
+    }
+    pub fn push_back(&mut self, val: T) {
+        self.back.push(val);
+    }
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.front.is_empty() {
+            self.rebalance((self.len() + 1) / 2);
+        }
+        self.front.pop()
+    }
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.back.is_empty() {
+            self.rebalance(self.len() / 2);
+        }
+        self.back.pop()
+    }
+    /// 先頭 `mid` 個を front に、残りを back に積み直す
+    fn rebalance(&mut self, mid: usize) {
+        let mut vals = Vec::with_capacity(self.len());
+        while let Some(val) = self.front.pop() {
+            vals.push(val);
+        }
+        let start = vals.len();
+        while let Some(val) = self.back.pop() {
+            vals.push(val);
+        }
+        vals[start..].reverse();
+        let back = vals.split_off(mid);
+        for val in vals.into_iter().rev() {
+            self.front.push(val);
+        }
+        for val in back {
+            self.back.push(val);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_random() {
+        let mut deque = DequeAggregation::new(String::new, |a, b| format!("{}{}", a, b));
+        let mut naive = std::collections::VecDeque::new();
+        let mut seed = 2;
+        for _ in 0..2000 {
+            let c = ((b'a' + (xorshift(&mut seed) % 26) as u8) as char).to_string();
+            match xorshift(&mut seed) % 4 {
+                0 => assert_eq!(deque.pop_front(), naive.pop_front()),
+                1 => assert_eq!(deque.pop_back(), naive.pop_back()),
+                2 => {
+                    deque.push_front(c.clone());
+                    naive.push_front(c);
+                }
+                _ => {
+                    deque.push_back(c.clone());
+                    naive.push_back(c);
+                }
+            }
+            assert_eq!(deque.len(), naive.len());
+            assert_eq!(deque.prod_all(), naive.iter().cloned().collect::<String>());
+        }
+    }
+}
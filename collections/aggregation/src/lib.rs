@@ -1,45 +1,61 @@
-/// 累積和をもつ stack
-pub struct StackAggregation<T, I, F> {
-    accum: Vec<T>,
-    #[allow(unused)]
-    id: I,
-    id_: T,
-    prod: F,
+mod deque;
+mod queue;
+mod sliding;
+mod stack;
+#[cfg(test)]
+mod test_util;
+
+pub use deque::{DequeAggregation, MonoidDequeAggregation};
+pub use queue::{MonoidQueueAggregation, QueueAggregation};
+pub use sliding::{SliceExt, Sliding, SlidingWindow};
+pub use stack::{MonoidStackAggregation, StackAggregation};
+
+use std::marker::PhantomData;
+
+/// `segtree::Monoid` と同じく、`id` を単位元とし `prod` が結合的であるもの
+pub trait Monoid {
+    type Item: Clone;
+    fn id() -> Self::Item;
+    fn prod(a: &Self::Item, b: &Self::Item) -> Self::Item;
 }
 
-impl<T, I: Fn() -> T, F: Fn(&T, &T) -> T> StackAggregation<T, I, F> {
-    pub fn new(id: I, prod: F) -> Self {
-        Self {
-            accum: vec![],
-            id_: id(),
-            id,
-            prod,
-        }
-    }
-    // 空なら`id`
-    pub fn prod_all(&self) -> &T {
-        self.accum.last().unwrap_or(&self.id_)
+/// 各 stack がもつ積
+pub trait Prod<T> {
+    fn prod(&self, a: &T, b: &T) -> T;
+}
+
+impl<T, F: Fn(&T, &T) -> T> Prod<T> for F {
+    fn prod(&self, a: &T, b: &T) -> T {
+        self(a, b)
     }
-    // 空なら `false`
-    pub fn pop(&mut self) -> bool {
-        self.accum.pop().is_some()
+}
+
+/// `Monoid` から作る `Prod`
+pub struct ByMonoid<M>(PhantomData<M>);
+
+impl<M> Default for ByMonoid<M> {
+    fn default() -> Self {
+        ByMonoid(PhantomData)
     }
-    pub fn push(&mut self, val: &T) {
-        self.accum.push((self.prod)(self.prod_all(), val))
+}
+
+impl<M> Clone for ByMonoid<M> {
+    fn clone(&self) -> Self {
+        ByMonoid(PhantomData)
     }
 }
 
-impl<T, I: Fn() -> T, F: Fn(&T, &T) -> T> Extend<T> for StackAggregation<T, I, F> {
-    fn extend<It: IntoIterator<Item = T>>(&mut self, iter: It) {
-        let mut iter = iter.into_iter();
-        while let Some(val) = iter.next() {
-            if self.accum.len() == self.accum.capacity() {
-                self.accum.reserve(iter.size_hint().0);
-            }
-            self.push(&val);
-        }
+impl<M: Monoid> Prod<M::Item> for ByMonoid<M> {
+    fn prod(&self, a: &M::Item, b: &M::Item) -> M::Item {
+        M::prod(a, b)
     }
 }
 
-#[cfg(test)]
-mod tests {}
+/// 引数の順を入れ替えた積
+pub(crate) struct Flip<F>(pub(crate) F);
+
+impl<T, F: Prod<T>> Prod<T> for Flip<F> {
+    fn prod(&self, a: &T, b: &T) -> T {
+        self.0.prod(b, a)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{ByMonoid, Flip, Monoid, Prod, StackAggregation};
+
+/// Sliding Window Aggregation
+/// 2 つの `StackAggregation` で queue を表し、`push_back`, `pop_front`, `prod_all` をならし O(1) で処理する
+/// `prod` は結合的であればよく、可換である必要はない
+pub struct QueueAggregation<T, F> {
+    /// 先頭が末尾にある
+    front: StackAggregation<T, Flip<F>>,
+    back: StackAggregation<T, F>,
+}
+
+/// `Monoid` によって定まる `QueueAggregation`
+pub type MonoidQueueAggregation<M> = QueueAggregation<<M as Monoid>::Item, ByMonoid<M>>;
+
+impl<T, F: Fn(&T, &T) -> T + Clone> QueueAggregation<T, F> {
+    /// `prod` は 2 つの stack で共有するので `Clone` である必要がある
+    pub fn new<I: Fn() -> T>(id: I, prod: F) -> Self {
+        Self::with_prod(id, prod)
+    }
+}
+
+impl<M: Monoid> MonoidQueueAggregation<M> {
+    pub fn new_monoid() -> Self {
+        Self::with_prod(M::id, ByMonoid::default())
+    }
+}
+
+impl<T, F: Prod<T>> QueueAggregation<T, F> {
+    fn with_prod<I: Fn() -> T>(id: I, prod: F) -> Self
+    where
+        F: Clone,
+    {
+        Self {
+            front: StackAggregation::with_prod(id(), Flip(prod.clone())),
+            back: StackAggregation::with_prod(id(), prod),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// 空なら `id`
+    pub fn prod_all(&self) -> T {
+        self.back
+            .prod
+            .prod(self.front.prod_all(), self.back.prod_all())
+    }
+    pub fn push_back(&mut self, val: T) {
+        self.back.push(val);
+    }
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.front.is_empty() {
+            while let Some(val) = self.back.pop() {
+                self.front.push(val);
+            }
+        }
+        self.front.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_random() {
+        // 文字列の連結は可換でない
+        let mut queue = QueueAggregation::new(String::new, |a, b| format!("{}{}", a, b));
+        let mut naive = std::collections::VecDeque::new();
+        let mut seed = 1;
+        for _ in 0..1000 {
+            if xorshift(&mut seed) % 3 == 0 {
+                assert_eq!(queue.pop_front(), naive.pop_front());
+            } else {
+                let c = ((b'a' + (xorshift(&mut seed) % 26) as u8) as char).to_string();
+                queue.push_back(c.clone());
+                naive.push_back(c);
+            }
+            assert_eq!(queue.len(), naive.len());
+            assert_eq!(queue.prod_all(), naive.iter().cloned().collect::<String>());
+        }
+    }
+
+    #[test]
+    fn test_monoid() {
+        /// 2x2 行列の積 (mod 2^64)
+        struct Matrix;
+        impl Monoid for Matrix {
+            type Item = [u64; 4];
+            fn id() -> [u64; 4] {
+                [1, 0, 0, 1]
+            }
+            fn prod(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+                let f = |i: usize, j: usize| {
+                    a[2 * i]
+                        .wrapping_mul(b[j])
+                        .wrapping_add(a[2 * i + 1].wrapping_mul(b[2 + j]))
+                };
+                [f(0, 0), f(0, 1), f(1, 0), f(1, 1)]
+            }
+        }
+        let mut queue = MonoidQueueAggregation::<Matrix>::new_monoid();
+        let mut naive = std::collections::VecDeque::new();
+        let mut seed = 3;
+        for _ in 0..500 {
+            if xorshift(&mut seed) % 3 == 0 {
+                assert_eq!(queue.pop_front(), naive.pop_front());
+            } else {
+                let mut m = [0; 4];
+                m.iter_mut().for_each(|x| *x = xorshift(&mut seed) % 5);
+                queue.push_back(m);
+                naive.push_back(m);
+            }
+            let expected = naive
+                .iter()
+                .fold(Matrix::id(), |acc, m| Matrix::prod(&acc, m));
+            assert_eq!(queue.prod_all(), expected);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::VecDeque;
+
+/// 単調 deque による、幅が変わる窓の最小値 (最大値)
+/// `push_back` と `pop_front` はならし O(1)、`get` は O(1)
+pub struct SlidingWindow<T, F> {
+    /// `(何番目に push したか, 値)`、値は `less` について狭義単調増加
+    deque: VecDeque<(usize, T)>,
+    /// 窓の先頭が何番目に push したものか
+    head: usize,
+    /// これまでに push した個数
+    tail: usize,
+    less: F,
+}
+
+impl<T, F: Fn(&T, &T) -> bool> SlidingWindow<T, F> {
+    /// `less` についての最小値を管理する
+    pub fn new(less: F) -> Self {
+        Self {
+            deque: VecDeque::new(),
+            head: 0,
+            tail: 0,
+            less,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.tail - self.head
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// 窓の最小値、空なら `None`
+    pub fn get(&self) -> Option<&T> {
+        self.deque.front().map(|(_, val)| val)
+    }
+    pub fn push_back(&mut self, val: T) {
+        while let Some((_, back)) = self.deque.back() {
+            if (self.less)(back, &val) {
+                break;
+            }
+            self.deque.pop_back();
+        }
+        self.deque.push_back((self.tail, val));
+        self.tail += 1;
+    }
+    /// 窓の先頭を取り除く
+    pub fn pop_front(&mut self) {
+        assert!(!self.is_empty(), "empty window");
+        if self.deque.front().unwrap().0 == self.head {
+            self.deque.pop_front();
+        }
+        self.head += 1;
+    }
+}
+
+impl<T: Ord> SlidingWindow<T, fn(&T, &T) -> bool> {
+    pub fn min() -> Self {
+        Self::new(|a, b| a < b)
+    }
+    pub fn max() -> Self {
+        Self::new(|a, b| a > b)
+    }
+}
+
+pub trait SliceExt {
+    type Item;
+    /// 幅 `k` の各窓の最小値を、窓の左端の順に列挙する
+    /// ```
+    /// use aggregation::SliceExt;
+    ///
+    /// let a = [3, 1, 4, 1, 5, 9, 2];
+    /// assert_eq!(a.sliding_min(3).collect::<Vec<_>>(), vec![&1, &1, &1, &1, &2]);
+    /// ```
+    fn sliding_min(&self, k: usize) -> Sliding<Self::Item>;
+    /// 幅 `k` の各窓の最大値を、窓の左端の順に列挙する
+    fn sliding_max(&self, k: usize) -> Sliding<Self::Item>;
+}
+
+impl<T: Ord> SliceExt for [T] {
+    type Item = T;
+    fn sliding_min(&self, k: usize) -> Sliding<T> {
+        Sliding::new(self, k, |a, b| a < b)
+    }
+    fn sliding_max(&self, k: usize) -> Sliding<T> {
+        Sliding::new(self, k, |a, b| a > b)
+    }
+}
+
+/// `SliceExt::sliding_min`, `SliceExt::sliding_max` が返す iterator
+pub struct Sliding<'a, T> {
+    slice: &'a [T],
+    k: usize,
+    /// 次に窓に入れる添字
+    next: usize,
+    window: SlidingWindow<&'a T, fn(&&'a T, &&'a T) -> bool>,
+}
+
+impl<'a, T> Sliding<'a, T> {
+    fn new(slice: &'a [T], k: usize, less: fn(&&'a T, &&'a T) -> bool) -> Self {
+        assert!(k > 0, "window width must be positive");
+        let mut window = SlidingWindow::new(less);
+        let next = (k - 1).min(slice.len());
+        for val in &slice[..next] {
+            window.push_back(val);
+        }
+        Self {
+            slice,
+            k,
+            next,
+            window,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Sliding<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        let val = self.slice.get(self.next)?;
+        self.window.push_back(val);
+        if self.window.len() > self.k {
+            self.window.pop_front();
+        }
+        self.next += 1;
+        self.window.get().copied()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.slice.len() - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for Sliding<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_window() {
+        let mut seed = 1;
+        let mut min = SlidingWindow::min();
+        let mut max = SlidingWindow::max();
+        let mut naive = VecDeque::new();
+        for _ in 0..1000 {
+            if !naive.is_empty() && xorshift(&mut seed) % 3 == 0 {
+                min.pop_front();
+                max.pop_front();
+                naive.pop_front();
+            } else {
+                let x = xorshift(&mut seed) % 20;
+                min.push_back(x);
+                max.push_back(x);
+                naive.push_back(x);
+            }
+            assert_eq!(min.len(), naive.len());
+            assert_eq!(min.get(), naive.iter().min());
+            assert_eq!(max.get(), naive.iter().max());
+        }
+    }
+
+    #[test]
+    fn test_slice() {
+        let mut seed = 2;
+        for &n in &[0, 1, 5, 30] {
+            let a: Vec<u64> = (0..n).map(|_| xorshift(&mut seed) % 10).collect();
+            for k in 1..n + 2 {
+                let min: Vec<_> = a.windows(k).map(|w| w.iter().min().unwrap()).collect();
+                let max: Vec<_> = a.windows(k).map(|w| w.iter().max().unwrap()).collect();
+                assert_eq!(a.sliding_min(k).len(), min.len());
+                assert_eq!(a.sliding_min(k).collect::<Vec<_>>(), min);
+                assert_eq!(a.sliding_max(k).collect::<Vec<_>>(), max);
+            }
+        }
+    }
+}
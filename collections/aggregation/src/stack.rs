@@ -1,45 +1,151 @@
-/// 累積和をもつ stack
-pub struct StackAggregation<T, I, F> {
+use crate::{ByMonoid, Monoid, Prod};
+use std::{iter::FromIterator, ops::Index, slice, vec};
+
+/// 累積積をもつ stack
+pub struct StackAggregation<T, F> {
+    vals: Vec<T>,
+    /// `accum[i]` は `vals[..=i]` の積
     accum: Vec<T>,
-    #[allow(unused)]
-    id: I,
-    id_: T,
-    prod: F,
+    id: T,
+    pub(crate) prod: F,
+}
+
+/// `Monoid` によって定まる `StackAggregation`、`FromIterator` を実装する
+pub type MonoidStackAggregation<M> = StackAggregation<<M as Monoid>::Item, ByMonoid<M>>;
+
+impl<T, F: Fn(&T, &T) -> T> StackAggregation<T, F> {
+    pub fn new<I: Fn() -> T>(id: I, prod: F) -> Self {
+        Self::with_prod(id(), prod)
+    }
 }
 
-impl<T, I: Fn() -> T, F: Fn(&T, &T) -> T> StackAggregation<T, I, F> {
-    pub fn new(id: I, prod: F) -> Self {
+impl<M: Monoid> MonoidStackAggregation<M> {
+    pub fn new_monoid() -> Self {
+        Self::with_prod(M::id(), ByMonoid::default())
+    }
+}
+
+impl<T, F: Prod<T>> StackAggregation<T, F> {
+    pub(crate) fn with_prod(id: T, prod: F) -> Self {
         Self {
+            vals: vec![],
             accum: vec![],
-            id_: id(),
             id,
             prod,
         }
     }
-    // 空なら`id`
+    pub fn len(&self) -> usize {
+        self.vals.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.vals.is_empty()
+    }
+    /// 空なら `id`
     pub fn prod_all(&self) -> &T {
-        self.accum.last().unwrap_or(&self.id_)
+        self.accum.last().unwrap_or(&self.id)
+    }
+    pub fn last(&self) -> Option<&T> {
+        self.vals.last()
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        self.accum.pop();
+        self.vals.pop()
+    }
+    pub fn push(&mut self, val: T) {
+        self.accum.push(self.prod.prod(self.prod_all(), &val));
+        self.vals.push(val);
     }
-    // 空なら `false`
-    pub fn pop(&mut self) -> bool {
-        self.accum.pop().is_some()
+    /// 底から順に列挙する
+    pub fn iter(&self) -> slice::Iter<T> {
+        self.vals.iter()
     }
-    pub fn push(&mut self, val: &T) {
-        self.accum.push((self.prod)(self.prod_all(), val))
+}
+
+impl<T, F: Prod<T>> Index<usize> for StackAggregation<T, F> {
+    type Output = T;
+    fn index(&self, i: usize) -> &T {
+        &self.vals[i]
     }
 }
 
-impl<T, I: Fn() -> T, F: Fn(&T, &T) -> T> Extend<T> for StackAggregation<T, I, F> {
+impl<T, F: Prod<T>> Extend<T> for StackAggregation<T, F> {
     fn extend<It: IntoIterator<Item = T>>(&mut self, iter: It) {
-        let mut iter = iter.into_iter();
-        while let Some(val) = iter.next() {
-            if self.accum.len() == self.accum.capacity() {
-                self.accum.reserve(iter.size_hint().0);
-            }
-            self.push(&val);
+        let iter = iter.into_iter();
+        let additional = iter.size_hint().0;
+        self.vals.reserve(additional);
+        self.accum.reserve(additional);
+        for val in iter {
+            self.push(val);
         }
     }
 }
 
+impl<M: Monoid> FromIterator<M::Item> for MonoidStackAggregation<M> {
+    fn from_iter<It: IntoIterator<Item = M::Item>>(iter: It) -> Self {
+        let mut stack = Self::new_monoid();
+        stack.extend(iter);
+        stack
+    }
+}
+
+impl<T, F> IntoIterator for StackAggregation<T, F> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+    fn into_iter(self) -> vec::IntoIter<T> {
+        self.vals.into_iter()
+    }
+}
+
+impl<'a, T, F> IntoIterator for &'a StackAggregation<T, F> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.vals.iter()
+    }
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack() {
+        let mut stack = StackAggregation::new(|| 0, |a, b| a + b);
+        stack.extend(vec![3, 1, 4]);
+        assert_eq!(*stack.prod_all(), 8);
+        assert_eq!(stack.pop(), Some(4));
+        stack.push(5);
+        assert_eq!(*stack.prod_all(), 9);
+        assert_eq!((stack.len(), stack[1], stack.last()), (3, 1, Some(&5)));
+        assert_eq!(stack.iter().copied().collect::<Vec<_>>(), vec![3, 1, 5]);
+        assert_eq!(stack.pop(), Some(5));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+        assert_eq!(*stack.prod_all(), 0);
+    }
+
+    #[test]
+    fn test_monoid() {
+        /// 文字列の連結
+        struct Concat;
+        impl Monoid for Concat {
+            type Item = String;
+            fn id() -> String {
+                String::new()
+            }
+            fn prod(a: &String, b: &String) -> String {
+                format!("{}{}", a, b)
+            }
+        }
+        let mut stack: MonoidStackAggregation<Concat> =
+            ["ab", "c", "de"].iter().map(|&s| s.to_string()).collect();
+        assert_eq!(stack.prod_all(), "abcde");
+        stack.pop();
+        stack.push("f".to_string());
+        assert_eq!(stack.prod_all(), "abcf");
+        assert_eq!((&stack).into_iter().map(String::len).sum::<usize>(), 4);
+        assert_eq!(stack.into_iter().collect::<Vec<_>>(), vec!["ab", "c", "f"]);
+    }
+}
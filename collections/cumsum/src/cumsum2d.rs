@@ -0,0 +1,163 @@
+use crate::range_from;
+use std::ops::{Range, RangeBounds};
+
+/// 二次元累積和
+pub struct Cumsum2D<T, S> {
+    /// `cum[i][j]` は `vec[..i]` の各行の `[..j]` の和
+    cum: Vec<Vec<T>>,
+    sub: S,
+}
+
+impl<T: Clone, S: Fn(&T, &T) -> T> Cumsum2D<T, S> {
+    /// O(HW)
+    pub fn new<A: Fn(&T, &T) -> T>(vec: Vec<Vec<T>>, zero: T, add: A, sub: S) -> Self {
+        let height = vec.len();
+        let width = vec.first().map_or(0, Vec::len);
+        let mut cum = vec![vec![zero; width + 1]; height + 1];
+        for (i, row) in vec.iter().enumerate() {
+            assert_eq!(row.len(), width, "not a rectangle");
+            for (j, x) in row.iter().enumerate() {
+                let s = add(&add(&cum[i][j + 1], &cum[i + 1][j]), x);
+                cum[i + 1][j + 1] = sub(&s, &cum[i][j]);
+            }
+        }
+        Self { cum, sub }
+    }
+    pub fn height(&self) -> usize {
+        self.cum.len() - 1
+    }
+    pub fn width(&self) -> usize {
+        self.cum[0].len() - 1
+    }
+    /// O(1)
+    /// `sub(_, _)`: `3` 回
+    pub fn sum(&self, rows: impl RangeBounds<usize>, cols: impl RangeBounds<usize>) -> T {
+        let Range { start: i0, end: i1 } = range_from(self.height(), rows);
+        let Range { start: j0, end: j1 } = range_from(self.width(), cols);
+        let (cum, sub) = (&self.cum, &self.sub);
+        sub(
+            &sub(&cum[i1][j1], &cum[i0][j1]),
+            &sub(&cum[i1][j0], &cum[i0][j0]),
+        )
+    }
+}
+
+/// 二次元 imos 法
+/// 長方形への加算をまとめて行い、最後に `build` で O(HW) かけて確定させる
+pub struct Imos2D<T, A, S> {
+    diff: Vec<Vec<T>>,
+    add: A,
+    sub: S,
+}
+
+impl<T: Clone, A: Fn(&T, &T) -> T, S: Fn(&T, &T) -> T> Imos2D<T, A, S> {
+    /// すべて `zero` である `height` 行 `width` 列の表
+    pub fn new(height: usize, width: usize, zero: T, add: A, sub: S) -> Self {
+        Self {
+            diff: vec![vec![zero; width + 1]; height + 1],
+            add,
+            sub,
+        }
+    }
+    pub fn height(&self) -> usize {
+        self.diff.len() - 1
+    }
+    pub fn width(&self) -> usize {
+        self.diff[0].len() - 1
+    }
+    /// O(1)
+    pub fn add_rect(
+        &mut self,
+        rows: impl RangeBounds<usize>,
+        cols: impl RangeBounds<usize>,
+        x: &T,
+    ) {
+        let Range { start: i0, end: i1 } = range_from(self.height(), rows);
+        let Range { start: j0, end: j1 } = range_from(self.width(), cols);
+        let (diff, add, sub) = (&mut self.diff, &self.add, &self.sub);
+        diff[i0][j0] = add(&diff[i0][j0], x);
+        diff[i0][j1] = sub(&diff[i0][j1], x);
+        diff[i1][j0] = sub(&diff[i1][j0], x);
+        diff[i1][j1] = add(&diff[i1][j1], x);
+    }
+    /// O(HW)
+    pub fn build(self) -> Vec<Vec<T>> {
+        let Self { mut diff, add, .. } = self;
+        diff.pop();
+        for row in &mut diff {
+            row.pop();
+            for j in 1..row.len() {
+                row[j] = add(&row[j - 1], &row[j]);
+            }
+        }
+        for i in 1..diff.len() {
+            for j in 0..diff[i].len() {
+                diff[i][j] = add(&diff[i - 1][j], &diff[i][j]);
+            }
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xorshift(seed: &mut u64) -> u64 {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        *seed
+    }
+
+    #[test]
+    fn test_sum() {
+        let mut seed = 1;
+        for &(h, w) in &[(0, 0), (1, 1), (3, 5), (4, 4), (2, 0)] {
+            let grid: Vec<Vec<i64>> = (0..h)
+                .map(|_| (0..w).map(|_| (xorshift(&mut seed) % 100) as i64).collect())
+                .collect();
+            let cum = Cumsum2D::new(grid.clone(), 0, |a, b| a + b, |a, b| a - b);
+            assert_eq!((cum.height(), cum.width()), (h, w));
+            for i0 in 0..=h {
+                for i1 in i0..=h {
+                    for j0 in 0..=w {
+                        for j1 in j0..=w {
+                            let naive: i64 = grid[i0..i1].iter().flat_map(|row| &row[j0..j1]).sum();
+                            assert_eq!(cum.sum(i0..i1, j0..j1), naive);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_imos() {
+        let mut seed = 2;
+        let (h, w) = (5, 7);
+        let mut naive = vec![vec![0i64; w]; h];
+        let mut imos = Imos2D::new(h, w, 0, |a, b| a + b, |a, b| a - b);
+        for _ in 0..50 {
+            let (i0, i1) = (
+                xorshift(&mut seed) as usize % (h + 1),
+                xorshift(&mut seed) as usize % (h + 1),
+            );
+            let (j0, j1) = (
+                xorshift(&mut seed) as usize % (w + 1),
+                xorshift(&mut seed) as usize % (w + 1),
+            );
+            let (i0, i1, j0, j1) = (i0.min(i1), i0.max(i1), j0.min(j1), j0.max(j1));
+            let x = (xorshift(&mut seed) % 100) as i64 - 50;
+            imos.add_rect(i0..i1, j0..j1, &x);
+            for row in &mut naive[i0..i1] {
+                row[j0..j1].iter_mut().for_each(|v| *v += x);
+            }
+        }
+        imos.add_rect(.., 2..=2, &1000);
+        for row in &mut naive {
+            row[2] += 1000;
+        }
+        assert_eq!(imos.build(), naive);
+    }
+}
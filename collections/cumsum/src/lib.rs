@@ -1,29 +1,107 @@
+mod cumsum2d;
+
+pub use cumsum2d::{Cumsum2D, Imos2D};
+
+use std::{
+    cmp::Ordering,
+    ops::{Bound, Range, RangeBounds},
+};
+
+/// 累積和
 pub struct Cumsum<T, S> {
+    /// `cum[i]` は `vec[..i]` の和
     cum: Vec<T>,
     sub: S,
 }
 
 impl<T: Clone, S: Fn(&T, &T) -> T> Cumsum<T, S> {
-    /// `vec[0].clone()`: `1` 回
-    /// `add(_, _)`: `n-1` 回
-    pub fn new<A: Fn(&T, &T) -> T>(vec: Vec<T>, add: A, sub: S) -> Self {
-        let mut cum = Vec::with_capacity(vec.len());
-        cum.push(vec[0].clone());
-        for i in 1..vec.len() {
-            cum.push(add(&cum[i - 1], &vec[i]));
+    /// `add(_, _)`: `n` 回
+    pub fn new<A: Fn(&T, &T) -> T>(vec: Vec<T>, zero: T, add: A, sub: S) -> Self {
+        let mut cum = Vec::with_capacity(vec.len() + 1);
+        cum.push(zero);
+        for (i, x) in vec.iter().enumerate() {
+            cum.push(add(&cum[i], x));
         }
         Self { cum, sub }
     }
-    /// `T::clone()`: `if l==0 {1} else {0}` 回
-    /// `add(_, _)`: `if l==0 {0} else {1}` 回
-    pub fn sum(&self, l: usize, r: usize) -> T {
-        if l == 0 {
-            self.cum[r].clone()
+    pub fn len(&self) -> usize {
+        self.cum.len() - 1
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// `sub(_, _)`: `1` 回
+    pub fn sum(&self, range: impl RangeBounds<usize>) -> T {
+        let Range { start, end } = range_from(self.len(), range);
+        (self.sub)(&self.cum[end], &self.cum[start])
+    }
+    /// `sum(..i) >= x` となる最小の `i`、なければ `None`
+    /// `sum(..i)` が `i` について単調非減少である必要がある
+    /// O(log n)
+    pub fn lower_bound(&self, x: &T) -> Option<usize>
+    where
+        T: PartialOrd,
+    {
+        let i = match self
+            .cum
+            .binary_search_by(|c| c.partial_cmp(x).unwrap().then(Ordering::Greater))
+        {
+            Ok(i) | Err(i) => i,
+        };
+        if i < self.cum.len() {
+            Some(i)
         } else {
-            (self.sub)(&self.cum[r], &self.cum[l - 1])
+            None
         }
     }
 }
 
+fn range_from(len: usize, range: impl RangeBounds<usize>) -> Range<usize> {
+    use Bound::*;
+    let start = match range.start_bound() {
+        Included(&a) => a,
+        Excluded(&a) => a + 1,
+        Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Excluded(&a) => a,
+        Included(&a) => a + 1,
+        Unbounded => len,
+    };
+    assert!(start <= end, "invalid range: {}..{}", start, end);
+    assert!(end <= len, "index out: {}/{}", end, len);
+    Range { start, end }
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sum() {
+        let vec = vec![3, 1, 4, 1, 5, 9, 2, 6];
+        let cum = Cumsum::new(vec.clone(), 0, |a, b| a + b, |a, b| a - b);
+        assert_eq!(cum.len(), 8);
+        for l in 0..=vec.len() {
+            for r in l..=vec.len() {
+                assert_eq!(cum.sum(l..r), vec[l..r].iter().sum());
+            }
+        }
+        assert_eq!(cum.sum(..), 31);
+        assert_eq!(cum.sum(2..=4), 10);
+
+        let empty = Cumsum::new(vec![], 0, |a: &i32, b| a + b, |a, b| a - b);
+        assert!(empty.is_empty());
+        assert_eq!(empty.sum(..), 0);
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        let vec = vec![3, 0, 4, 1, 0, 9];
+        let cum = Cumsum::new(vec.clone(), 0, |a, b| a + b, |a, b| a - b);
+        for x in 0..20 {
+            let naive = (0..=vec.len()).find(|&i| vec[..i].iter().sum::<u32>() >= x);
+            assert_eq!(cum.lower_bound(&x), naive);
+        }
+    }
+}
@@ -1,3 +1,15 @@
+mod persistent;
+mod rollback;
+#[cfg(test)]
+mod test_util;
+mod weighted;
+mod with;
+
+pub use persistent::PersistentDisjointSet;
+pub use rollback::{offline_dynamic_connectivity, Event, RollbackDisjointSet, Snapshot};
+pub use weighted::{AbelianGroup, Contradiction, WeightedDisjointSet};
+pub use with::DisjointSetWith;
+
 pub struct DisjointSet {
     parent: Vec<usize>,
     size: Vec<usize>,
@@ -37,7 +49,79 @@ impl DisjointSet {
         self.num_groups -= 1;
         true
     }
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.root_of(a) == self.root_of(b)
+    }
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+    pub fn num_groups(&self) -> usize {
+        self.num_groups
+    }
+    /// `self.labels()` の順に並べた各グループの根
+    pub fn leaders(&mut self) -> Vec<usize> {
+        let mut seen = vec![false; self.len()];
+        let mut leaders = Vec::with_capacity(self.num_groups);
+        for a in 0..self.len() {
+            let root = self.root_of(a);
+            if !seen[root] {
+                seen[root] = true;
+                leaders.push(root);
+            }
+        }
+        leaders
+    }
+    /// 各頂点の属するグループの番号
+    /// 番号は `0..self.num_groups()` で、最小の頂点が小さいグループほど小さい
+    pub fn labels(&mut self) -> Vec<usize> {
+        let mut label = vec![usize::max_value(); self.len()];
+        let mut next = 0;
+        (0..self.len())
+            .map(|a| {
+                let root = self.root_of(a);
+                if label[root] == usize::max_value() {
+                    label[root] = next;
+                    next += 1;
+                }
+                label[root]
+            })
+            .collect()
+    }
+    /// `self.labels()` の順に並べたグループ、各グループは昇順
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut groups = vec![vec![]; self.num_groups];
+        for (a, label) in self.labels().into_iter().enumerate() {
+            groups[label].push(a);
+        }
+        groups
+    }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups() {
+        let mut ds = DisjointSet::new(6);
+        assert_eq!(ds.num_groups(), 6);
+        assert!(ds.merge(4, 1));
+        assert!(ds.merge(3, 0));
+        assert!(ds.merge(1, 5));
+        assert!(!ds.merge(5, 4));
+        assert_eq!(ds.num_groups(), 3);
+        assert!(ds.same(4, 5));
+        assert!(!ds.same(0, 1));
+        assert_eq!(ds.size_of(5), 3);
+        assert_eq!(ds.labels(), vec![0, 1, 2, 0, 1, 1]);
+        assert_eq!(ds.groups(), vec![vec![0, 3], vec![1, 4, 5], vec![2]]);
+        let leaders = ds.leaders();
+        assert_eq!(leaders.len(), 3);
+        for (leader, group) in leaders.into_iter().zip(ds.groups()) {
+            assert!(group.contains(&leader));
+        }
+    }
+}
@@ -0,0 +1,107 @@
+/// 部分永続 Union-Find
+/// `merge` を呼ぶたびに時刻が `1` 進み、過去の任意の時刻の状態を O(log n) で調べられる
+pub struct PersistentDisjointSet {
+    parent: Vec<usize>,
+    /// 根でなくなった時刻、根なら `usize::max_value()`
+    since: Vec<usize>,
+    /// 根である間の `(時刻, その時刻以降の大きさ)`
+    size: Vec<Vec<(usize, usize)>>,
+    now: usize,
+}
+
+impl PersistentDisjointSet {
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            since: vec![usize::max_value(); n],
+            size: vec![vec![(0, 1)]; n],
+            now: 0,
+        }
+    }
+    /// これまでに `merge` を呼んだ回数
+    pub fn now(&self) -> usize {
+        self.now
+    }
+    /// 時刻 `t`、すなわち `t` 回目の `merge` の直後における根
+    pub fn root_at(&self, mut a: usize, t: usize) -> usize {
+        while self.since[a] <= t {
+            a = self.parent[a];
+        }
+        a
+    }
+    pub fn root_of(&self, a: usize) -> usize {
+        self.root_at(a, self.now)
+    }
+    pub fn same_at(&self, a: usize, b: usize, t: usize) -> bool {
+        self.root_at(a, t) == self.root_at(b, t)
+    }
+    pub fn same(&self, a: usize, b: usize) -> bool {
+        self.same_at(a, b, self.now)
+    }
+    pub fn size_at(&self, a: usize, t: usize) -> usize {
+        let history = &self.size[self.root_at(a, t)];
+        let i = match history.binary_search_by_key(&t, |&(time, _)| time) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        history[i].1
+    }
+    pub fn size_of(&self, a: usize) -> usize {
+        self.size_at(a, self.now)
+    }
+    /// 時刻を `1` 進める
+    /// return if merged
+    pub fn merge(&mut self, a: usize, b: usize) -> bool {
+        self.now += 1;
+        let (mut a, mut b) = (self.root_of(a), self.root_of(b));
+        if a == b {
+            return false;
+        }
+        if self.size_of(a) < self.size_of(b) {
+            std::mem::swap(&mut a, &mut b);
+        }
+        let size = self.size_of(a) + self.size_of(b);
+        self.parent[b] = a;
+        self.since[b] = self.now;
+        self.size[a].push((self.now, size));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+    use crate::DisjointSet;
+
+    #[test]
+    fn test_persistent() {
+        let mut seed = 1;
+        let n = 20;
+        let mut ds = PersistentDisjointSet::new(n);
+        let mut snapshots = vec![DisjointSet::new(n)];
+        let mut merges = vec![];
+        for _ in 0..30 {
+            let (a, b) = (
+                xorshift(&mut seed) as usize % n,
+                xorshift(&mut seed) as usize % n,
+            );
+            let mut naive = DisjointSet::new(n);
+            merges.push((a, b));
+            for &(a, b) in &merges[..merges.len() - 1] {
+                naive.merge(a, b);
+            }
+            assert_eq!(ds.merge(a, b), naive.merge(a, b));
+            snapshots.push(naive);
+        }
+        assert_eq!(ds.now(), 30);
+        for (t, naive) in snapshots.iter_mut().enumerate() {
+            for a in 0..n {
+                assert_eq!(ds.size_at(a, t), naive.size_of(a));
+                for b in 0..n {
+                    assert_eq!(ds.same_at(a, b, t), naive.same(a, b));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+/// 操作を取り消せる Union-Find
+/// 経路圧縮をしないので、各操作は O(log n)
+pub struct RollbackDisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    num_groups: usize,
+    /// 各 `merge` で `(親, 子)` とした根の組、併合しなかったら `None`
+    history: Vec<Option<(usize, usize)>>,
+}
+
+/// `RollbackDisjointSet::snapshot` の時点を表す
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Snapshot(usize);
+
+impl RollbackDisjointSet {
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            num_groups: n,
+            history: vec![],
+        }
+    }
+    pub fn root_of(&self, mut a: usize) -> usize {
+        while self.parent[a] != a {
+            a = self.parent[a];
+        }
+        a
+    }
+    pub fn size_of(&self, a: usize) -> usize {
+        self.size[self.root_of(a)]
+    }
+    pub fn same(&self, a: usize, b: usize) -> bool {
+        self.root_of(a) == self.root_of(b)
+    }
+    pub fn num_groups(&self) -> usize {
+        self.num_groups
+    }
+    /// return if merged
+    pub fn merge(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.root_of(a), self.root_of(b));
+        if a == b {
+            self.history.push(None);
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.num_groups -= 1;
+        self.history.push(Some((a, b)));
+        true
+    }
+    /// 最後の `merge` を取り消す、併合しなかった `merge` も 1 回と数える
+    /// 取り消す `merge` がなければ `false`
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            None => false,
+            Some(None) => true,
+            Some(Some((a, b))) => {
+                self.parent[b] = b;
+                self.size[a] -= self.size[b];
+                self.num_groups += 1;
+                true
+            }
+        }
+    }
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.history.len())
+    }
+    /// `snapshot` を取った時点の状態に戻す
+    pub fn rollback(&mut self, snapshot: Snapshot) {
+        assert!(snapshot.0 <= self.history.len(), "invalid snapshot");
+        while self.history.len() > snapshot.0 {
+            self.undo();
+        }
+    }
+}
+
+/// `offline_dynamic_connectivity` に与える出来事
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    /// 辺を加える、多重辺も可
+    Add(usize, usize),
+    /// 辺を 1 本取り除く、その辺が存在している必要がある
+    Remove(usize, usize),
+    /// 2 頂点が連結か
+    Query(usize, usize),
+}
+
+/// 頂点数 `n` の無向グラフに `events` を順に起こしたときの、各 `Event::Query` の答え
+/// O(q log q log n)
+pub fn offline_dynamic_connectivity(n: usize, events: &[Event]) -> Vec<bool> {
+    let len = events.len();
+    let size = len.next_power_of_two();
+    // 時間についての segment tree の各節点に、その区間のあいだ存在する辺を置く
+    let mut edges = vec![vec![]; 2 * size];
+    let mut add_edge = |start: usize, end: usize, edge: (usize, usize)| {
+        let (mut start, mut end) = (start + size, end + size);
+        while start < end {
+            if start & 1 != 0 {
+                edges[start].push(edge);
+                start += 1;
+            }
+            if end & 1 != 0 {
+                end -= 1;
+                edges[end].push(edge);
+            }
+            start >>= 1;
+            end >>= 1;
+        }
+    };
+    let mut added: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (t, &event) in events.iter().enumerate() {
+        match event {
+            Event::Add(a, b) => added.entry((a.min(b), a.max(b))).or_default().push(t),
+            Event::Remove(a, b) => {
+                let edge = (a.min(b), a.max(b));
+                let start = added
+                    .get_mut(&edge)
+                    .and_then(Vec::pop)
+                    .unwrap_or_else(|| panic!("no such edge: {:?}", edge));
+                add_edge(start, t, edge);
+            }
+            Event::Query(..) => {}
+        }
+    }
+    for (edge, starts) in added {
+        for start in starts {
+            add_edge(start, len, edge);
+        }
+    }
+
+    fn dfs(
+        k: usize,
+        size: usize,
+        events: &[Event],
+        edges: &[Vec<(usize, usize)>],
+        ds: &mut RollbackDisjointSet,
+        ans: &mut Vec<bool>,
+    ) {
+        if k >= size && k - size >= events.len() {
+            return;
+        }
+        let snapshot = ds.snapshot();
+        for &(a, b) in &edges[k] {
+            ds.merge(a, b);
+        }
+        if k >= size {
+            if let Event::Query(a, b) = events[k - size] {
+                ans.push(ds.same(a, b));
+            }
+        } else {
+            dfs(2 * k, size, events, edges, ds, ans);
+            dfs(2 * k + 1, size, events, edges, ds, ans);
+        }
+        ds.rollback(snapshot);
+    }
+    let mut ans = vec![];
+    if len != 0 {
+        dfs(
+            1,
+            size,
+            events,
+            &edges,
+            &mut RollbackDisjointSet::new(n),
+            &mut ans,
+        );
+    }
+    ans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_rollback() {
+        let mut ds = RollbackDisjointSet::new(4);
+        ds.merge(0, 1);
+        let snapshot = ds.snapshot();
+        ds.merge(2, 3);
+        ds.merge(1, 2);
+        assert!(!ds.merge(0, 3));
+        assert_eq!(ds.num_groups(), 1);
+        assert!(ds.undo());
+        assert!(ds.same(0, 3));
+        assert!(ds.undo());
+        assert!(!ds.same(0, 3));
+        assert!(ds.same(2, 3));
+        ds.rollback(snapshot);
+        assert!(!ds.same(2, 3));
+        assert!(ds.same(0, 1));
+        assert_eq!(ds.size_of(1), 2);
+        assert_eq!(ds.num_groups(), 3);
+        assert!(ds.undo());
+        assert!(!ds.undo());
+    }
+
+    #[test]
+    fn test_dynamic_connectivity() {
+        let mut seed = 1;
+        let n = 8;
+        let mut events = vec![];
+        let mut naive = vec![];
+        let mut expected = vec![];
+        for _ in 0..300 {
+            let a = xorshift(&mut seed) as usize % n;
+            let b = xorshift(&mut seed) as usize % n;
+            match xorshift(&mut seed) % 3 {
+                0 => {
+                    events.push(Event::Add(a, b));
+                    naive.push((a, b));
+                }
+                1 if !naive.is_empty() => {
+                    let (a, b) = naive.swap_remove(xorshift(&mut seed) as usize % naive.len());
+                    events.push(Event::Remove(b, a));
+                }
+                _ => {
+                    events.push(Event::Query(a, b));
+                    let mut ds = RollbackDisjointSet::new(n);
+                    for &(a, b) in &naive {
+                        ds.merge(a, b);
+                    }
+                    expected.push(ds.same(a, b));
+                }
+            }
+        }
+        assert_eq!(offline_dynamic_connectivity(n, &events), expected);
+    }
+}
@@ -0,0 +1,168 @@
+/// 可換群
+pub trait AbelianGroup: Clone + PartialEq {
+    fn zero() -> Self;
+    fn add(&self, rhs: &Self) -> Self;
+    fn neg(&self) -> Self;
+    fn sub(&self, rhs: &Self) -> Self {
+        self.add(&rhs.neg())
+    }
+}
+
+macro_rules! impl_abelian_group {
+    ($($t:ty),*) => {$(
+        impl AbelianGroup for $t {
+            fn zero() -> $t {
+                0
+            }
+            fn add(&self, rhs: &$t) -> $t {
+                self + rhs
+            }
+            fn neg(&self) -> $t {
+                -self
+            }
+            fn sub(&self, rhs: &$t) -> $t {
+                self - rhs
+            }
+        }
+    )*};
+}
+impl_abelian_group!(i8, i16, i32, i64, i128, isize);
+
+/// `merge` で与えた制約が既存の制約と矛盾した
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Contradiction;
+
+/// ポテンシャル付き Union-Find
+/// 各頂点 `a` に値 `x[a]` があるとし、`x[b] - x[a] = w` という制約を管理する
+pub struct WeightedDisjointSet<W = i64> {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    /// `x[a] - x[parent[a]]`
+    potential: Vec<W>,
+    num_groups: usize,
+}
+
+impl<W: AbelianGroup> WeightedDisjointSet<W> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            potential: vec![W::zero(); n],
+            num_groups: n,
+        }
+    }
+    pub fn root_of(&mut self, a: usize) -> usize {
+        self.find(a).0
+    }
+    pub fn size_of(&mut self, a: usize) -> usize {
+        let a = self.root_of(a);
+        self.size[a]
+    }
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.root_of(a) == self.root_of(b)
+    }
+    pub fn num_groups(&self) -> usize {
+        self.num_groups
+    }
+    /// 同じグループなら `x[b] - x[a]`
+    pub fn diff(&mut self, a: usize, b: usize) -> Option<W> {
+        let (ra, wa) = self.find(a);
+        let (rb, wb) = self.find(b);
+        if ra == rb {
+            Some(wb.sub(&wa))
+        } else {
+            None
+        }
+    }
+    /// `x[b] - x[a] = w` という制約を加える
+    /// return if merged, or `Err` if contradicted
+    pub fn merge(&mut self, a: usize, b: usize, w: W) -> Result<bool, Contradiction> {
+        let (mut ra, wa) = self.find(a);
+        let (mut rb, wb) = self.find(b);
+        if ra == rb {
+            return if wb.sub(&wa) == w {
+                Ok(false)
+            } else {
+                Err(Contradiction)
+            };
+        }
+        // x[rb] - x[ra]
+        let mut w = w.add(&wa).sub(&wb);
+        if self.size[ra] < self.size[rb] {
+            std::mem::swap(&mut ra, &mut rb);
+            w = w.neg();
+        }
+        self.parent[rb] = ra;
+        self.potential[rb] = w;
+        self.size[ra] += self.size[rb];
+        self.num_groups -= 1;
+        Ok(true)
+    }
+    /// `(根, x[a] - x[根])`
+    fn find(&mut self, a: usize) -> (usize, W) {
+        let p = self.parent[a];
+        if p == a {
+            return (a, W::zero());
+        }
+        let (root, w) = self.find(p);
+        self.parent[a] = root;
+        self.potential[a] = self.potential[a].add(&w);
+        (root, self.potential[a].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_weighted() {
+        let mut ds = WeightedDisjointSet::new(5);
+        assert_eq!(ds.merge(0, 1, 3), Ok(true));
+        assert_eq!(ds.merge(2, 1, -2), Ok(true));
+        assert_eq!(ds.merge(3, 4, 10), Ok(true));
+        // x = [0, 3, 5, ?, ? + 10]
+        assert_eq!(ds.diff(0, 2), Some(5));
+        assert_eq!(ds.diff(2, 0), Some(-5));
+        assert_eq!(ds.diff(0, 3), None);
+        assert_eq!(ds.merge(0, 2, 5), Ok(false));
+        assert_eq!(ds.merge(0, 2, 4), Err(Contradiction));
+        assert_eq!(ds.merge(4, 1, 1), Ok(true));
+        // x = [0, 3, 5, -8, 2]
+        assert_eq!(ds.diff(3, 2), Some(13));
+        assert_eq!(ds.num_groups(), 1);
+        assert_eq!(ds.size_of(3), 5);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut seed = 1;
+        let n = 30;
+        let x: Vec<i64> = (0..n)
+            .map(|_| (xorshift(&mut seed) % 1000) as i64)
+            .collect();
+        let mut ds = WeightedDisjointSet::<i64>::new(n);
+        let mut naive: Vec<usize> = (0..n).collect();
+        for _ in 0..200 {
+            let a = xorshift(&mut seed) as usize % n;
+            let b = xorshift(&mut seed) as usize % n;
+            if xorshift(&mut seed) % 2 == 0 {
+                let merged = naive[a] != naive[b];
+                assert_eq!(ds.merge(a, b, x[b] - x[a]), Ok(merged));
+                let (from, to) = (naive[b], naive[a]);
+                naive
+                    .iter_mut()
+                    .filter(|g| **g == from)
+                    .for_each(|g| *g = to);
+            } else if naive[a] == naive[b] {
+                assert_eq!(ds.diff(a, b), Some(x[b] - x[a]));
+                if a != b {
+                    assert_eq!(ds.merge(a, b, x[b] - x[a] + 1), Err(Contradiction));
+                }
+            } else {
+                assert_eq!(ds.diff(a, b), None);
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::DisjointSet;
+
+/// 各グループが値をもつ Union-Find
+/// `merge(a, b)` で併合したとき、新しいグループの値は `f(a の値, b の値)`
+pub struct DisjointSetWith<T, F> {
+    ds: DisjointSet,
+    /// 根でなければ `None`
+    data: Vec<Option<T>>,
+    f: F,
+}
+
+impl<T, F: FnMut(T, T) -> T> DisjointSetWith<T, F> {
+    /// 頂点 `a` のみからなるグループの値を `data[a]` とする
+    pub fn new(data: Vec<T>, f: F) -> Self {
+        Self {
+            ds: DisjointSet::new(data.len()),
+            data: data.into_iter().map(Some).collect(),
+            f,
+        }
+    }
+    pub fn root_of(&mut self, a: usize) -> usize {
+        self.ds.root_of(a)
+    }
+    pub fn size_of(&mut self, a: usize) -> usize {
+        self.ds.size_of(a)
+    }
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.ds.same(a, b)
+    }
+    pub fn num_groups(&self) -> usize {
+        self.ds.num_groups()
+    }
+    /// `a` の属するグループの値
+    pub fn data(&mut self, a: usize) -> &T {
+        let a = self.root_of(a);
+        self.data[a].as_ref().unwrap()
+    }
+    pub fn data_mut(&mut self, a: usize) -> &mut T {
+        let a = self.root_of(a);
+        self.data[a].as_mut().unwrap()
+    }
+    /// return if merged
+    pub fn merge(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.root_of(a), self.root_of(b));
+        if !self.ds.merge(a, b) {
+            return false;
+        }
+        let da = self.data[a].take().unwrap();
+        let db = self.data[b].take().unwrap();
+        let root = self.root_of(a);
+        self.data[root] = Some((self.f)(da, db));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_sum_min() {
+        let mut ds = DisjointSetWith::new(vec![(5, 0), (3, 1), (8, 2), (1, 3)], |a, b| {
+            let (sa, ma): (i32, usize) = a;
+            let (sb, mb) = b;
+            (sa + sb, ma.min(mb))
+        });
+        assert!(ds.merge(2, 1));
+        assert_eq!(*ds.data(1), (11, 1));
+        assert!(ds.merge(3, 2));
+        assert!(!ds.merge(1, 3));
+        assert_eq!(*ds.data(2), (12, 1));
+        ds.data_mut(0).0 += 100;
+        assert_eq!(*ds.data(0), (105, 0));
+        assert!(ds.merge(1, 0));
+        assert_eq!(*ds.data(3), (117, 0));
+        assert_eq!(ds.num_groups(), 1);
+    }
+
+    #[test]
+    fn test_small_to_large() {
+        let colors = vec![1, 2, 1, 3, 2];
+        let mut ds = DisjointSetWith::new(
+            colors
+                .iter()
+                .map(|&c| std::iter::once(c).collect())
+                .collect(),
+            |mut a: BTreeSet<i32>, mut b: BTreeSet<i32>| {
+                if a.len() < b.len() {
+                    std::mem::swap(&mut a, &mut b);
+                }
+                a.extend(b);
+                a
+            },
+        );
+        ds.merge(0, 2);
+        assert_eq!(ds.data(2).len(), 1);
+        ds.merge(1, 2);
+        ds.merge(3, 4);
+        assert_eq!(ds.data(0).len(), 2);
+        ds.merge(4, 0);
+        assert_eq!(
+            ds.data(1).iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+    }
+}
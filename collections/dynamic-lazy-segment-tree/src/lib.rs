@@ -1 +1,287 @@
+use {
+    lazysegtree::Type,
+    std::ops::{Bound, Range, RangeBounds},
+};
 
+/// 添字が `i64` の区間 `start..end` である遅延セグメント木
+/// 節点は触れたときにはじめて作られるので、`0..10^18` などの巨大な区間も扱える
+/// 初期値はすべて `T::id()`
+pub struct DynamicLazySegTree<T: Type> {
+    range: Range<i64>,
+    nodes: Vec<Node<T>>,
+}
+
+/// `prod` には `lazy` が作用済み
+/// 子がない節点の各要素は、`T::id()` に `lazy` を作用させたもの
+struct Node<T: Type> {
+    prod: T::Item,
+    lazy: Option<T::Operator>,
+    /// 子がなければ `0`、あれば左の子の添字で、右の子は `left + 1`
+    left: usize,
+}
+
+impl<T: Type> Node<T> {
+    fn new() -> Self {
+        Self {
+            prod: T::id(),
+            lazy: None,
+            left: 0,
+        }
+    }
+}
+
+impl<T: Type> DynamicLazySegTree<T> {
+    pub fn new(range: Range<i64>) -> Self {
+        assert!(range.start < range.end, "empty range: {:?}", range);
+        Self {
+            range,
+            nodes: vec![Node::new()],
+        }
+    }
+    /// 添字の範囲
+    pub fn range(&self) -> Range<i64> {
+        self.range.clone()
+    }
+    /// 全体の積
+    pub fn prod(&self) -> &T::Item {
+        &self.nodes[0].prod
+    }
+    pub fn prod_range(&self, range: impl RangeBounds<i64>) -> T::Item {
+        let Range { start, end } = self.range_from(range);
+        if start == end {
+            return T::id();
+        }
+        let Range { start: l, end: r } = self.range;
+        self.prod_rec(0, l, r, start, end)
+    }
+    pub fn get(&self, i: i64) -> T::Item {
+        self.prod_range(i..=i)
+    }
+    pub fn set(&mut self, i: i64, val: T::Item) {
+        let _ = self.range_from(i..=i);
+        let Range { start: l, end: r } = self.range;
+        self.set_rec(0, l, r, i, val);
+    }
+    pub fn operate(&mut self, range: impl RangeBounds<i64>, op: &T::Operator) {
+        let Range { start, end } = self.range_from(range);
+        if start == end {
+            return;
+        }
+        let Range { start: l, end: r } = self.range;
+        self.operate_rec(0, l, r, start, end, op);
+    }
+    fn range_from(&self, range: impl RangeBounds<i64>) -> Range<i64> {
+        use Bound::*;
+        let start = match range.start_bound() {
+            Included(&start) => start,
+            Excluded(&start) => start + 1,
+            Unbounded => self.range.start,
+        };
+        let end = match range.end_bound() {
+            Excluded(&end) => end,
+            Included(&end) => end + 1,
+            Unbounded => self.range.end,
+        };
+        assert!(start <= end, "invalid range: {}..{}", start, end);
+        assert!(
+            self.range.start <= start && end <= self.range.end,
+            "index out of bounds: {}..{} / {:?}",
+            start,
+            end,
+            self.range
+        );
+        Range { start, end }
+    }
+    fn compose_lazy(&mut self, node: usize, op: &T::Operator, len: usize) {
+        let node = &mut self.nodes[node];
+        T::operate_with_len(&mut node.prod, op, len);
+        node.lazy = Some(match node.lazy.take() {
+            Some(lazy) => T::composition(&lazy, op),
+            None => op.clone(),
+        });
+    }
+    /// 子がなければ作り、`lazy` を子に伝える
+    fn propagate(&mut self, node: usize, l: i64, r: i64) {
+        if self.nodes[node].left == 0 {
+            self.nodes[node].left = self.nodes.len();
+            self.nodes.push(Node::new());
+            self.nodes.push(Node::new());
+        }
+        if let Some(lazy) = self.nodes[node].lazy.take() {
+            let mid = mid(l, r);
+            let left = self.nodes[node].left;
+            self.compose_lazy(left, &lazy, width(l, mid));
+            self.compose_lazy(left + 1, &lazy, width(mid, r));
+        }
+    }
+    fn update(&mut self, node: usize) {
+        let left = self.nodes[node].left;
+        self.nodes[node].prod = T::prod(&self.nodes[left].prod, &self.nodes[left + 1].prod);
+    }
+    fn prod_rec(&self, node: usize, l: i64, r: i64, start: i64, end: i64) -> T::Item {
+        let Node { prod, lazy, left } = &self.nodes[node];
+        if (start, end) == (l, r) {
+            return prod.clone();
+        }
+        if *left == 0 {
+            let mut ret = T::id();
+            if let Some(lazy) = lazy {
+                T::operate_with_len(&mut ret, lazy, width(start, end));
+            }
+            return ret;
+        }
+        let mid = mid(l, r);
+        let mut ret = if end <= mid {
+            self.prod_rec(*left, l, mid, start, end)
+        } else if mid <= start {
+            self.prod_rec(left + 1, mid, r, start, end)
+        } else {
+            T::prod(
+                &self.prod_rec(*left, l, mid, start, mid),
+                &self.prod_rec(left + 1, mid, r, mid, end),
+            )
+        };
+        if let Some(lazy) = lazy {
+            T::operate_with_len(&mut ret, lazy, width(start, end));
+        }
+        ret
+    }
+    fn set_rec(&mut self, node: usize, l: i64, r: i64, i: i64, val: T::Item) {
+        if width(l, r) == 1 {
+            self.nodes[node].prod = val;
+            self.nodes[node].lazy = None;
+            return;
+        }
+        self.propagate(node, l, r);
+        let mid = mid(l, r);
+        let left = self.nodes[node].left;
+        if i < mid {
+            self.set_rec(left, l, mid, i, val);
+        } else {
+            self.set_rec(left + 1, mid, r, i, val);
+        }
+        self.update(node);
+    }
+    fn operate_rec(&mut self, node: usize, l: i64, r: i64, start: i64, end: i64, op: &T::Operator) {
+        if (start, end) == (l, r) {
+            return self.compose_lazy(node, op, width(l, r));
+        }
+        self.propagate(node, l, r);
+        let mid = mid(l, r);
+        let left = self.nodes[node].left;
+        if end <= mid {
+            self.operate_rec(left, l, mid, start, end, op);
+        } else if mid <= start {
+            self.operate_rec(left + 1, mid, r, start, end, op);
+        } else {
+            self.operate_rec(left, l, mid, start, mid, op);
+            self.operate_rec(left + 1, mid, r, mid, end, op);
+        }
+        self.update(node);
+    }
+}
+
+/// `l..r` の要素数、`i64::MIN..i64::MAX` のような区間でも溢れない
+fn width(l: i64, r: i64) -> usize {
+    (i128::from(r) - i128::from(l)) as usize
+}
+
+/// `l..r` を二分する位置
+fn mid(l: i64, r: i64) -> i64 {
+    l + ((i128::from(r) - i128::from(l)) / 2) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub enum SumAdd {}
+    impl Type for SumAdd {
+        type Item = i128;
+        type Operator = i128;
+        fn id() -> i128 {
+            0
+        }
+        fn prod(a: &i128, b: &i128) -> i128 {
+            a + b
+        }
+        fn composition(a: &i128, b: &i128) -> i128 {
+            a + b
+        }
+        fn operate_with_len(val: &mut i128, op: &i128, len: usize) {
+            *val += op * len as i128;
+        }
+    }
+
+    fn xorshift(seed: &mut u64) -> u64 {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        *seed
+    }
+
+    #[test]
+    fn test_small() {
+        let mut seed = 1;
+        let (lo, hi) = (-20i64, 17i64);
+        let mut naive = vec![0i128; (hi - lo) as usize];
+        let mut seg = DynamicLazySegTree::<SumAdd>::new(lo..hi);
+        for _ in 0..300 {
+            let a = lo + (xorshift(&mut seed) % (hi - lo + 1) as u64) as i64;
+            let b = lo + (xorshift(&mut seed) % (hi - lo + 1) as u64) as i64;
+            let (a, b) = (a.min(b), a.max(b));
+            let x = (xorshift(&mut seed) % 100) as i128 - 50;
+            match xorshift(&mut seed) % 3 {
+                0 => {
+                    seg.operate(a..b, &x);
+                    for v in &mut naive[(a - lo) as usize..(b - lo) as usize] {
+                        *v += x;
+                    }
+                }
+                1 if a < hi => {
+                    seg.set(a, x);
+                    naive[(a - lo) as usize] = x;
+                }
+                _ => {
+                    let expected: i128 = naive[(a - lo) as usize..(b - lo) as usize].iter().sum();
+                    assert_eq!(seg.prod_range(a..b), expected);
+                }
+            }
+        }
+        for i in lo..hi {
+            assert_eq!(seg.get(i), naive[(i - lo) as usize]);
+        }
+        assert_eq!(*seg.prod(), naive.iter().sum());
+    }
+
+    #[test]
+    fn test_huge() {
+        let n = 1_000_000_000_000_000_000;
+        let mut seg = DynamicLazySegTree::<SumAdd>::new(-n..n);
+        seg.operate(.., &1);
+        seg.operate(0..n, &2);
+        seg.set(-5, 100);
+        seg.operate(..=-5, &-1);
+        assert_eq!(seg.prod_range(..0), 103);
+        assert_eq!(seg.prod_range(0..), 3 * n as i128);
+        assert_eq!(seg.get(-5), 99);
+        assert_eq!(seg.get(-4), 1);
+        assert_eq!(seg.get(n - 1), 3);
+        assert_eq!(seg.prod_range(-6..-3), 100);
+        assert!(seg.nodes.len() < 1000);
+    }
+
+    #[test]
+    fn test_full_range() {
+        let mut seg = DynamicLazySegTree::<SumAdd>::new(std::i64::MIN..std::i64::MAX);
+        seg.operate(0..10, &1);
+        seg.operate(.., &2);
+        seg.set(std::i64::MIN, 5);
+        seg.set(std::i64::MAX - 1, 7);
+        assert_eq!(seg.prod_range(-3..3), 2 * 6 + 3);
+        assert_eq!(seg.get(std::i64::MIN), 5);
+        assert_eq!(seg.get(std::i64::MAX - 1), 7);
+        let len = (1i128 << 64) - 1;
+        assert_eq!(*seg.prod(), 2 * (len - 2) + 10 + 5 + 7);
+    }
+}
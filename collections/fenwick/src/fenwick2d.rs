@@ -0,0 +1,104 @@
+use crate::{range_from, AbelianGroup};
+use std::ops::{Range, RangeBounds};
+
+/// 二次元 Fenwick Tree
+/// 一点加算と長方形領域の和を O(log H log W) で処理する
+pub struct Fenwick2D<T> {
+    height: usize,
+    width: usize,
+    /// 一次元の場合と同様に、各行と各列で 1-indexed
+    tree: Vec<Vec<T>>,
+}
+
+impl<T: AbelianGroup> Fenwick2D<T> {
+    /// すべて `T::zero()` である `height` 行 `width` 列の表
+    pub fn new(height: usize, width: usize) -> Self {
+        Self {
+            height,
+            width,
+            tree: vec![vec![T::zero(); width + 1]; height + 1],
+        }
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// `(i, j)` に `x` を足す
+    pub fn add(&mut self, i: usize, j: usize, x: &T) {
+        assert!(i < self.height, "index out: {}/{}", i, self.height);
+        assert!(j < self.width, "index out: {}/{}", j, self.width);
+        let mut i = i + 1;
+        while i <= self.height {
+            let row = &mut self.tree[i];
+            let mut j = j + 1;
+            while j <= self.width {
+                row[j] = row[j].add(x);
+                j += j & j.wrapping_neg();
+            }
+            i += i & i.wrapping_neg();
+        }
+    }
+    /// `[..i][..j]` の和
+    pub fn prefix_sum(&self, i: usize, j: usize) -> T {
+        assert!(i <= self.height, "index out: {}/{}", i, self.height);
+        assert!(j <= self.width, "index out: {}/{}", j, self.width);
+        let mut ret = T::zero();
+        let mut i = i;
+        while i > 0 {
+            let row = &self.tree[i];
+            let mut j = j;
+            while j > 0 {
+                ret = ret.add(&row[j]);
+                j &= j - 1;
+            }
+            i &= i - 1;
+        }
+        ret
+    }
+    pub fn sum(&self, rows: impl RangeBounds<usize>, cols: impl RangeBounds<usize>) -> T {
+        let Range { start: i0, end: i1 } = range_from(self.height, rows);
+        let Range { start: j0, end: j1 } = range_from(self.width, cols);
+        self.prefix_sum(i1, j1)
+            .sub(&self.prefix_sum(i0, j1))
+            .sub(&self.prefix_sum(i1, j0))
+            .add(&self.prefix_sum(i0, j0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_random() {
+        let mut seed = 4;
+        for &(h, w) in &[(1, 1), (3, 5), (4, 4), (2, 0)] {
+            let mut naive = vec![vec![0i64; w]; h];
+            let mut bit = Fenwick2D::new(h, w);
+            for _ in 0..100 {
+                if w > 0 && xorshift(&mut seed) % 2 == 0 {
+                    let (i, j) = (
+                        xorshift(&mut seed) as usize % h,
+                        xorshift(&mut seed) as usize % w,
+                    );
+                    let x = (xorshift(&mut seed) % 100) as i64 - 50;
+                    bit.add(i, j, &x);
+                    naive[i][j] += x;
+                }
+                for i0 in 0..=h {
+                    for i1 in i0..=h {
+                        for j0 in 0..=w {
+                            for j1 in j0..=w {
+                                let cells = naive[i0..i1].iter().flat_map(|row| &row[j0..j1]);
+                                assert_eq!(bit.sum(i0..i1, j0..j1), cells.sum());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
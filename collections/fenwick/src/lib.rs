@@ -0,0 +1,251 @@
+mod fenwick2d;
+mod range_add;
+#[cfg(test)]
+mod test_util;
+
+pub use fenwick2d::Fenwick2D;
+pub use range_add::RangeAddFenwick;
+
+use std::ops::{Bound, Range, RangeBounds};
+
+/// 可換群
+pub trait AbelianGroup: Clone {
+    fn zero() -> Self;
+    fn add(&self, rhs: &Self) -> Self;
+    fn neg(&self) -> Self;
+    fn sub(&self, rhs: &Self) -> Self {
+        self.add(&rhs.neg())
+    }
+    /// `self` を `n` 個足したもの
+    fn times(&self, mut n: usize) -> Self {
+        let (mut ret, mut pow) = (Self::zero(), self.clone());
+        while n > 0 {
+            if n & 1 == 1 {
+                ret = ret.add(&pow);
+            }
+            pow = pow.add(&pow);
+            n >>= 1;
+        }
+        ret
+    }
+}
+
+macro_rules! impl_abelian_group_signed {
+    ($($t:ty),*) => {$(
+        impl AbelianGroup for $t {
+            fn zero() -> $t {
+                0
+            }
+            fn add(&self, rhs: &$t) -> $t {
+                self + rhs
+            }
+            fn neg(&self) -> $t {
+                -self
+            }
+            fn sub(&self, rhs: &$t) -> $t {
+                self - rhs
+            }
+            fn times(&self, n: usize) -> $t {
+                self * n as $t
+            }
+        }
+    )*};
+}
+impl_abelian_group_signed!(i8, i16, i32, i64, i128, isize);
+
+/// 途中の値が負になってもよいように、`2^bit` を法として扱う
+macro_rules! impl_abelian_group_unsigned {
+    ($($t:ty),*) => {$(
+        impl AbelianGroup for $t {
+            fn zero() -> $t {
+                0
+            }
+            fn add(&self, rhs: &$t) -> $t {
+                self.wrapping_add(*rhs)
+            }
+            fn neg(&self) -> $t {
+                self.wrapping_neg()
+            }
+            fn sub(&self, rhs: &$t) -> $t {
+                self.wrapping_sub(*rhs)
+            }
+            fn times(&self, n: usize) -> $t {
+                self.wrapping_mul(n as $t)
+            }
+        }
+    )*};
+}
+impl_abelian_group_unsigned!(u8, u16, u32, u64, u128, usize);
+
+/// Fenwick Tree (Binary Indexed Tree)
+/// 一点加算と区間和を O(log n) で処理する
+pub struct Fenwick<T> {
+    /// `tree[i]` は `[i - (i & i.wrapping_neg()), i)` の和、`tree[0]` は使わない
+    tree: Vec<T>,
+}
+
+impl<T: AbelianGroup> From<&[T]> for Fenwick<T> {
+    /// O(n)
+    fn from(slice: &[T]) -> Self {
+        let mut tree = Vec::with_capacity(slice.len() + 1);
+        tree.push(T::zero());
+        tree.extend_from_slice(slice);
+        for i in 1..tree.len() {
+            let j = i + (i & i.wrapping_neg());
+            if j < tree.len() {
+                tree[j] = tree[j].add(&tree[i]);
+            }
+        }
+        Self { tree }
+    }
+}
+
+impl<T: AbelianGroup> Fenwick<T> {
+    /// すべて `T::zero()` である長さ `n` の列
+    pub fn new(n: usize) -> Self {
+        Self {
+            tree: vec![T::zero(); n + 1],
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// `i` 番目に `x` を足す
+    pub fn add(&mut self, i: usize, x: &T) {
+        assert!(i < self.len(), "index out: {}/{}", i, self.len());
+        let mut i = i + 1;
+        while i < self.tree.len() {
+            self.tree[i] = self.tree[i].add(x);
+            i += i & i.wrapping_neg();
+        }
+    }
+    /// `[..end]` の和
+    pub fn prefix_sum(&self, end: usize) -> T {
+        assert!(end <= self.len(), "index out: {}/{}", end, self.len());
+        let mut ret = T::zero();
+        let mut i = end;
+        while i > 0 {
+            ret = ret.add(&self.tree[i]);
+            i &= i - 1;
+        }
+        ret
+    }
+    pub fn sum(&self, range: impl RangeBounds<usize>) -> T {
+        let Range { start, end } = range_from(self.len(), range);
+        self.prefix_sum(end).sub(&self.prefix_sum(start))
+    }
+    pub fn get(&self, i: usize) -> T {
+        self.sum(i..=i)
+    }
+    /// `prefix_sum(i) >= x` となる最小の `i`、なければ `None`
+    /// 各要素が非負である必要がある
+    /// O(log n)
+    pub fn lower_bound(&self, x: &T) -> Option<usize>
+    where
+        T: PartialOrd,
+    {
+        if *x <= T::zero() {
+            return Some(0);
+        }
+        let (mut pos, mut acc) = (0, T::zero());
+        let mut k = self.len().next_power_of_two();
+        while k > 0 {
+            if pos + k <= self.len() {
+                let next = acc.add(&self.tree[pos + k]);
+                if next < *x {
+                    pos += k;
+                    acc = next;
+                }
+            }
+            k >>= 1;
+        }
+        if pos < self.len() {
+            Some(pos + 1)
+        } else {
+            None
+        }
+    }
+}
+
+fn range_from(len: usize, range: impl RangeBounds<usize>) -> Range<usize> {
+    use Bound::*;
+    let start = match range.start_bound() {
+        Included(&a) => a,
+        Excluded(&a) => a + 1,
+        Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Excluded(&a) => a,
+        Included(&a) => a + 1,
+        Unbounded => len,
+    };
+    assert!(start <= end, "invalid range: {}..{}", start, end);
+    assert!(end <= len, "index out: {}/{}", end, len);
+    Range { start, end }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_random() {
+        let mut seed = 1;
+        for &n in &[0, 1, 2, 7, 8, 20] {
+            let mut naive: Vec<i64> = (0..n).map(|_| (xorshift(&mut seed) % 100) as i64).collect();
+            let mut bit = Fenwick::from(&naive[..]);
+            assert_eq!(bit.len(), n);
+            for _ in 0..100 {
+                let l = xorshift(&mut seed) as usize % (n + 1);
+                let r = xorshift(&mut seed) as usize % (n + 1);
+                let (l, r) = (l.min(r), l.max(r));
+                if l < n && xorshift(&mut seed) % 2 == 0 {
+                    let x = (xorshift(&mut seed) % 100) as i64 - 50;
+                    bit.add(l, &x);
+                    naive[l] += x;
+                    assert_eq!(bit.get(l), naive[l]);
+                } else {
+                    assert_eq!(bit.sum(l..r), naive[l..r].iter().sum());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        let mut seed = 2;
+        for &n in &[0, 1, 5, 8, 13] {
+            let vec: Vec<u32> = (0..n).map(|_| (xorshift(&mut seed) % 4) as u32).collect();
+            let bit = Fenwick::from(&vec[..]);
+            for x in 0..3 * n as u32 + 2 {
+                let naive = (0..=n).find(|&i| vec[..i].iter().sum::<u32>() >= x);
+                assert_eq!(bit.lower_bound(&x), naive);
+            }
+        }
+    }
+
+    #[test]
+    fn test_times() {
+        #[derive(Clone, PartialEq, Debug)]
+        struct Xor(u8);
+        impl AbelianGroup for Xor {
+            fn zero() -> Self {
+                Xor(0)
+            }
+            fn add(&self, rhs: &Self) -> Self {
+                Xor(self.0 ^ rhs.0)
+            }
+            fn neg(&self) -> Self {
+                self.clone()
+            }
+        }
+        assert_eq!(Xor(5).times(3), Xor(5));
+        assert_eq!(Xor(5).times(4), Xor(0));
+        assert_eq!(7i32.times(6), 42);
+        assert_eq!(3u32.neg().times(2).add(&10), 4);
+    }
+}
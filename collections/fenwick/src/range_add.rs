@@ -0,0 +1,90 @@
+use crate::{range_from, AbelianGroup, Fenwick};
+use std::ops::{Range, RangeBounds};
+
+/// 区間加算と区間和を O(log n) で処理する Fenwick Tree
+pub struct RangeAddFenwick<T> {
+    /// `prefix_sum(i) = b0.prefix_sum(i) + i * b1.prefix_sum(i)`
+    b0: Fenwick<T>,
+    b1: Fenwick<T>,
+}
+
+impl<T: AbelianGroup> From<&[T]> for RangeAddFenwick<T> {
+    /// O(n)
+    fn from(slice: &[T]) -> Self {
+        Self {
+            b0: Fenwick::from(slice),
+            b1: Fenwick::new(slice.len()),
+        }
+    }
+}
+
+impl<T: AbelianGroup> RangeAddFenwick<T> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            b0: Fenwick::new(n),
+            b1: Fenwick::new(n),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.b0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// `range` の各要素に `x` を足す
+    pub fn add(&mut self, range: impl RangeBounds<usize>, x: &T) {
+        let Range { start, end } = range_from(self.len(), range);
+        if start == end {
+            return;
+        }
+        self.b0.add(start, &x.times(start).neg());
+        self.b1.add(start, x);
+        if end < self.len() {
+            self.b0.add(end, &x.times(end));
+            self.b1.add(end, &x.neg());
+        }
+    }
+    /// `[..end]` の和
+    pub fn prefix_sum(&self, end: usize) -> T {
+        self.b0
+            .prefix_sum(end)
+            .add(&self.b1.prefix_sum(end).times(end))
+    }
+    pub fn sum(&self, range: impl RangeBounds<usize>) -> T {
+        let Range { start, end } = range_from(self.len(), range);
+        self.prefix_sum(end).sub(&self.prefix_sum(start))
+    }
+    pub fn get(&self, i: usize) -> T {
+        self.sum(i..=i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_random() {
+        let mut seed = 3;
+        for &n in &[0, 1, 6, 16] {
+            let mut naive: Vec<i64> = (0..n).map(|_| (xorshift(&mut seed) % 100) as i64).collect();
+            let mut bit = RangeAddFenwick::from(&naive[..]);
+            for _ in 0..200 {
+                let l = xorshift(&mut seed) as usize % (n + 1);
+                let r = xorshift(&mut seed) as usize % (n + 1);
+                let (l, r) = (l.min(r), l.max(r));
+                if xorshift(&mut seed) % 2 == 0 {
+                    let x = (xorshift(&mut seed) % 100) as i64 - 50;
+                    bit.add(l..r, &x);
+                    naive[l..r].iter_mut().for_each(|v| *v += x);
+                } else {
+                    assert_eq!(bit.sum(l..r), naive[l..r].iter().sum());
+                }
+            }
+            for (i, &v) in naive.iter().enumerate() {
+                assert_eq!(bit.get(i), v);
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{range_from, Type};
+use std::ops::{Range, RangeBounds};
+
+/// 節点を `Box` で持つ実装、`LazySegTree` との比較のためにテストでのみ使う
+pub struct BoxedLazySegTree<T: Type> {
+    len: usize,
+    root: Node<T>,
+}
+
+impl<T: Type> From<&[T::Item]> for BoxedLazySegTree<T> {
+    fn from(slice: &[T::Item]) -> Self {
+        // `Node` は空にできないので、空なら単位元の葉をおく
+        let root = if slice.is_empty() {
+            Node::Leaf { val: T::id() }
+        } else {
+            Node::from(slice)
+        };
+        Self {
+            len: slice.len(),
+            root,
+        }
+    }
+}
+
+impl<T: Type> BoxedLazySegTree<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn prod(&mut self) -> &T::Item {
+        self.root.prod()
+    }
+    pub fn prod_range(&mut self, range: impl RangeBounds<usize>) -> T::Item {
+        let Range { start, end } = range_from(self.len, range);
+        if start == end {
+            return T::id();
+        }
+        self.root.prod_range(start, end)
+    }
+    pub fn get(&mut self, i: usize) -> &T::Item {
+        assert!(i < self.len(), "index out: {}/{}", i, self.len());
+        self.root.get(i)
+    }
+    pub fn set(&mut self, i: usize, val: T::Item) {
+        assert!(i < self.len(), "index out: {}/{}", i, self.len());
+        self.root.set(i, val);
+    }
+    pub fn operate(&mut self, range: impl RangeBounds<usize>, op: &T::Operator) {
+        let Range { start, end } = range_from(self.len, range);
+        if start == end {
+            return;
+        }
+        self.root.operate(start, end, op)
+    }
+}
+
+enum Node<T: Type> {
+    Leaf {
+        val: T::Item,
+    },
+    Span {
+        len: usize,
+        prod: T::Item,
+        lazy: Option<T::Operator>,
+        left: Box<Self>,
+        right: Box<Self>,
+    },
+}
+
+impl<T: Type> From<&[T::Item]> for Node<T> {
+    fn from(slice: &[T::Item]) -> Self {
+        if slice.len() == 1 {
+            Node::Leaf {
+                val: slice[0].clone(),
+            }
+        } else {
+            let mid = slice.len() / 2;
+            let mut left = Self::from(&slice[..mid]);
+            let mut right = Self::from(&slice[mid..]);
+            Node::Span {
+                len: slice.len(),
+                prod: T::prod(left.prod(), right.prod()),
+                lazy: None,
+                left: Box::new(left),
+                right: Box::new(right),
+            }
+        }
+    }
+}
+
+impl<T: Type> Node<T> {
+    fn propagate(&mut self) {
+        if let Self::Span {
+            prod,
+            len,
+            lazy,
+            left,
+            right,
+        } = self
+        {
+            if let Some(lazy) = lazy.take() {
+                T::operate_with_len(prod, &lazy, *len);
+                left.compose_lazy(&lazy);
+                right.compose_lazy(&lazy);
+            }
+        }
+    }
+    fn compose_lazy(&mut self, op: &T::Operator) {
+        match self {
+            Self::Leaf { val } => T::operate(val, op),
+            Self::Span {
+                lazy: Some(lazy), ..
+            } => *lazy = T::composition(lazy, op),
+            Self::Span { lazy, .. } => *lazy = Some(op.clone()),
+        }
+    }
+    fn prod(&mut self) -> &T::Item {
+        self.propagate();
+        match self {
+            Self::Leaf { val } => val,
+            Self::Span { prod, .. } => prod,
+        }
+    }
+    fn len(&self) -> usize {
+        match self {
+            Self::Leaf { .. } => 1,
+            Self::Span { len, .. } => *len,
+        }
+    }
+    fn get(&mut self, i: usize) -> &T::Item {
+        self.propagate();
+        match self {
+            Self::Leaf { val } => val,
+            Self::Span { left, right, .. } => {
+                let mid = left.len();
+                if i < mid {
+                    left.get(i)
+                } else {
+                    right.get(i - mid)
+                }
+            }
+        }
+    }
+    fn set(&mut self, i: usize, v: T::Item) {
+        self.propagate();
+        match self {
+            Self::Leaf { val } => *val = v,
+            Self::Span {
+                prod, left, right, ..
+            } => {
+                let mid = left.len();
+                if i < mid {
+                    left.set(i, v)
+                } else {
+                    right.set(i - mid, v)
+                }
+                *prod = T::prod(left.prod(), right.prod());
+            }
+        }
+    }
+    fn operate(&mut self, start: usize, end: usize, op: &T::Operator) {
+        self.propagate();
+        match self {
+            Self::Leaf { val } => T::operate(val, op),
+            Self::Span {
+                len,
+                prod,
+                left,
+                right,
+                ..
+            } => {
+                if (start, end) == (0, *len) {
+                    return self.compose_lazy(op);
+                }
+                let mid = left.len();
+                if end <= mid {
+                    left.operate(start, end, op);
+                } else if mid <= start {
+                    right.operate(start - mid, end - mid, op);
+                } else {
+                    left.operate(start, mid, op);
+                    right.operate(0, end - mid, op);
+                }
+                *prod = T::prod(left.prod(), right.prod());
+            }
+        }
+    }
+    fn prod_range(&mut self, start: usize, end: usize) -> T::Item {
+        self.propagate();
+        match self {
+            Self::Leaf { val } => val.clone(),
+            Self::Span {
+                len,
+                prod,
+                left,
+                right,
+                ..
+            } => {
+                if (start, end) == (0, *len) {
+                    return prod.clone();
+                }
+                let mid = left.len();
+                if end <= mid {
+                    left.prod_range(start, end)
+                } else if mid <= start {
+                    right.prod_range(start - mid, end - mid)
+                } else {
+                    T::prod(
+                        &left.prod_range(start, mid),
+                        &right.prod_range(0, end - mid),
+                    )
+                }
+            }
+        }
+    }
+}
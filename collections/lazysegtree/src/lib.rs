@@ -1,3 +1,12 @@
+#[cfg(test)]
+mod boxed;
+#[cfg(test)]
+mod test_util;
+mod treap;
+pub mod types;
+
+pub use treap::ImplicitTreap;
+
 use std::ops::{Bound, Range, RangeBounds};
 
 pub trait Type {
@@ -13,17 +22,31 @@ pub trait Type {
     }
 }
 
+/// 配列上の遅延セグメント木
 pub struct LazySegTree<T: Type> {
     len: usize,
-    root: Node<T>,
+    size: usize,
+    log: u32,
+    data: Vec<T::Item>,
+    lazy: Vec<Option<T::Operator>>,
 }
 
 impl<T: Type> From<&[T::Item]> for LazySegTree<T> {
     fn from(slice: &[T::Item]) -> Self {
-        Self {
+        let size = slice.len().next_power_of_two();
+        let mut data = vec![T::id(); size * 2];
+        data[size..size + slice.len()].clone_from_slice(slice);
+        let mut seg = Self {
             len: slice.len(),
-            root: Node::from(slice),
+            size,
+            log: size.trailing_zeros(),
+            data,
+            lazy: vec![None; size],
+        };
+        for k in (1..size).rev() {
+            seg.update(k);
         }
+        seg
     }
 }
 
@@ -37,203 +60,413 @@ impl<T: Type> LazySegTree<T> {
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
-    pub fn prod(&mut self) -> &T::Item {
-        self.root.prod()
+    pub fn prod(&self) -> &T::Item {
+        &self.data[1]
     }
     pub fn prod_range(&mut self, range: impl RangeBounds<usize>) -> T::Item {
-        let Range { start, end } = self.range_from(range);
-        self.root.prod_range(start, end)
+        let Range { mut start, mut end } = range_from(self.len, range);
+        if start == end {
+            return T::id();
+        }
+        start += self.size;
+        end += self.size;
+        for i in (1..=self.log).rev() {
+            if (start >> i) << i != start {
+                self.push(start >> i);
+            }
+            if (end >> i) << i != end {
+                self.push((end - 1) >> i);
+            }
+        }
+        let mut left = T::id();
+        let mut right = T::id();
+        while start < end {
+            if start & 1 != 0 {
+                left = T::prod(&left, &self.data[start]);
+                start += 1;
+            }
+            if end & 1 != 0 {
+                end -= 1;
+                right = T::prod(&self.data[end], &right);
+            }
+            start >>= 1;
+            end >>= 1;
+        }
+        T::prod(&left, &right)
+    }
+    /// `prod_range` と同じ値を `&self` で求める
+    /// 遅延している作用を子に伝えず、戻りがけに合成する
+    pub fn peek_range(&self, range: impl RangeBounds<usize>) -> T::Item {
+        let Range { start, end } = range_from(self.len, range);
+        if start == end {
+            return T::id();
+        }
+        self.peek_rec(1, 0, self.size, start, end)
+    }
+    /// `get` と同じ値を `&self` で求める
+    pub fn peek(&self, i: usize) -> T::Item {
+        assert!(i < self.len(), "index out: {}/{}", i, self.len());
+        self.peek_range(i..=i)
     }
     pub fn get(&mut self, i: usize) -> &T::Item {
         assert!(i < self.len(), "index out: {}/{}", i, self.len());
-        self.root.get(i)
+        let p = i + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        &self.data[p]
     }
     pub fn set(&mut self, i: usize, val: T::Item) {
         assert!(i < self.len(), "index out: {}/{}", i, self.len());
-        self.root.set(i, val);
+        let p = i + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        self.data[p] = val;
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
     }
     pub fn operate(&mut self, range: impl RangeBounds<usize>, op: &T::Operator) {
-        let Range { start, end } = self.range_from(range);
-        self.root.operate(start, end, op)
-    }
-    fn range_from(&self, range: impl RangeBounds<usize>) -> Range<usize> {
-        use Bound::*;
-        let start = match range.start_bound() {
-            Included(&start) => start,
-            Excluded(&start) => start + 1,
-            Unbounded => 0,
-        };
-        let end = match range.end_bound() {
-            Excluded(&end) => end,
-            Included(&end) => end + 1,
-            Unbounded => self.len,
-        };
-        assert!(start <= end, "invalid range: {}..{}", start, end);
-        assert!(end <= self.len, "index out of bounds: {}/{}", end, self.len);
-        Range { start, end }
-    }
-}
-
-enum Node<T: Type> {
-    Leaf {
-        val: T::Item,
-    },
-    Span {
-        len: usize,
-        prod: T::Item,
-        lazy: Option<T::Operator>,
-        left: Box<Self>,
-        right: Box<Self>,
-    },
-}
-
-impl<T: Type> From<&[T::Item]> for Node<T> {
-    fn from(slice: &[T::Item]) -> Self {
-        if slice.len() == 1 {
-            Self::Leaf { val: slice[0].clone() }
-        } else {
-            let mid = slice.len() / 2;
-            let left = Self::from(&slice[..mid]);
-            let right = Self::from(&slice[mid..]);
-            Self::Span {
-                len: slice.len(),
-                prod: T::id(),
-                lazy: None,
-                left: Box::new(left),
-                right: Box::new(right),
+        let Range { mut start, mut end } = range_from(self.len, range);
+        if start == end {
+            return;
+        }
+        start += self.size;
+        end += self.size;
+        for i in (1..=self.log).rev() {
+            if (start >> i) << i != start {
+                self.push(start >> i);
+            }
+            if (end >> i) << i != end {
+                self.push((end - 1) >> i);
             }
         }
-    }
-}
-
-impl<T: Type> Node<T> {
-    fn propagate(&mut self) {
-        if let Self::Span { prod, len, lazy, left, right } = self {
-            if let Some(lazy) = lazy.take() {
-                T::operate_with_len(prod, &lazy, *len);
-                left.compose_lazy(&lazy);
-                right.compose_lazy(&lazy);
+        {
+            let (mut start, mut end) = (start, end);
+            while start < end {
+                if start & 1 != 0 {
+                    self.apply(start, op);
+                    start += 1;
+                }
+                if end & 1 != 0 {
+                    end -= 1;
+                    self.apply(end, op);
+                }
+                start >>= 1;
+                end >>= 1;
             }
         }
-    }
-    fn compose_lazy(&mut self, op: &T::Operator) {
-        match self {
-            Self::Leaf { val } => T::operate(val, op),
-            Self::Span { lazy: Some(lazy), .. } => *lazy = T::composition(lazy, op),
-            Self::Span { lazy, .. } => *lazy = Some(op.clone()),
+        for i in 1..=self.log {
+            if (start >> i) << i != start {
+                self.update(start >> i);
+            }
+            if (end >> i) << i != end {
+                self.update((end - 1) >> i);
+            }
         }
     }
-    fn prod(&mut self) -> &T::Item {
-        self.propagate();
-        match self {
-            Self::Leaf { val } => val,
-            Self::Span { prod, .. } => prod,
+    /// `pred(&self.prod_range(l..r))` が `true` となる最大の `r` を返す
+    /// `pred` は単調であり、`pred(&T::id())` は `true` である必要がある
+    pub fn max_right(&mut self, l: usize, mut pred: impl FnMut(&T::Item) -> bool) -> usize {
+        assert!(l <= self.len, "index out: {}/{}", l, self.len);
+        if l == self.len {
+            return self.len;
         }
-    }
-    fn len(&self) -> usize {
-        match self {
-            Self::Leaf { .. } => 1,
-            Self::Span { len, .. } => *len,
+        let mut l = l + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(l >> i);
         }
-    }
-    fn get(&mut self, i: usize) -> &T::Item {
-        self.propagate();
-        match self {
-            Self::Leaf { val } => val,
-            Self::Span { left, right, .. } => {
-                let mid = left.len();
-                if i < mid {
-                    left.get(i)
-                } else {
-                    right.get(i - mid)
+        let mut acc = T::id();
+        loop {
+            while l % 2 == 0 {
+                l >>= 1;
+            }
+            let next = T::prod(&acc, &self.data[l]);
+            if !pred(&next) {
+                while l < self.size {
+                    self.push(l);
+                    l *= 2;
+                    let next = T::prod(&acc, &self.data[l]);
+                    if pred(&next) {
+                        acc = next;
+                        l += 1;
+                    }
                 }
+                return l - self.size;
+            }
+            acc = next;
+            l += 1;
+            if l.is_power_of_two() {
+                return self.len;
             }
         }
     }
-    fn set(&mut self, i: usize, v: T::Item) {
-        self.propagate();
-        match self {
-            Self::Leaf { val } => *val = v,
-            Self::Span { prod, left, right, .. } => {
-                let mid = left.len();
-                if i < mid {
-                    left.set(i, v)
-                } else {
-                    right.set(i - mid, v)
+    /// `pred(&self.prod_range(l..r))` が `true` となる最小の `l` を返す
+    /// `pred` は単調であり、`pred(&T::id())` は `true` である必要がある
+    pub fn min_left(&mut self, r: usize, mut pred: impl FnMut(&T::Item) -> bool) -> usize {
+        assert!(r <= self.len, "index out: {}/{}", r, self.len);
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.size;
+        for i in (1..=self.log).rev() {
+            self.push((r - 1) >> i);
+        }
+        let mut acc = T::id();
+        loop {
+            r -= 1;
+            while r > 1 && r % 2 == 1 {
+                r >>= 1;
+            }
+            let next = T::prod(&self.data[r], &acc);
+            if !pred(&next) {
+                while r < self.size {
+                    self.push(r);
+                    r = 2 * r + 1;
+                    let next = T::prod(&self.data[r], &acc);
+                    if pred(&next) {
+                        acc = next;
+                        r -= 1;
+                    }
                 }
-                *prod = T::prod(left.prod(), right.prod());
+                return r + 1 - self.size;
+            }
+            acc = next;
+            if r.is_power_of_two() {
+                return 0;
             }
         }
     }
-    fn operate(&mut self, start: usize, end: usize, op: &T::Operator) {
-        self.propagate();
-        match self {
-            Self::Leaf { val } => T::operate(val, op),
-            Self::Span { len, left, right, .. } => {
-                if (start, end) == (0, *len) {
-                    return self.compose_lazy(op);
-                }
-                let mid = left.len();
-                if end <= mid {
-                    left.operate(start, end, op);
-                } else if mid <= start {
-                    right.operate(start - mid, end - mid, op);
-                } else {
-                    left.operate(start, mid, op);
-                    right.operate(0, end - mid, op);
-                }
-            }
+    /// 節点 `k` は区間 `l..r` を表し、`start..end` はその部分区間で空でない
+    fn peek_rec(&self, k: usize, l: usize, r: usize, start: usize, end: usize) -> T::Item {
+        if (start, end) == (l, r) {
+            return self.data[k].clone();
         }
+        let mid = (l + r) / 2;
+        let mut ret = if end <= mid {
+            self.peek_rec(2 * k, l, mid, start, end)
+        } else if mid <= start {
+            self.peek_rec(2 * k + 1, mid, r, start, end)
+        } else {
+            T::prod(
+                &self.peek_rec(2 * k, l, mid, start, mid),
+                &self.peek_rec(2 * k + 1, mid, r, mid, end),
+            )
+        };
+        if let Some(lazy) = &self.lazy[k] {
+            T::operate_with_len(&mut ret, lazy, end - start);
+        }
+        ret
     }
-    fn prod_range(&mut self, start: usize, end: usize) -> T::Item {
-        self.propagate();
-        match self {
-            Self::Leaf { val } => val.clone(),
-            Self::Span { len, prod, left, right, .. } => {
-                if (start, end) == (0, *len) {
-                    return prod.clone();
-                }
-                let mid = left.len();
-                if end <= mid {
-                    left.prod_range(start, end)
-                } else if mid <= start {
-                    right.prod_range(start - mid, end - mid)
-                } else {
-                    T::prod(&left.prod_range(start, mid), &right.prod_range(0, end - mid))
-                }
-            }
+    fn update(&mut self, k: usize) {
+        self.data[k] = T::prod(&self.data[2 * k], &self.data[2 * k + 1]);
+    }
+    /// 節点 `k` が表す区間の長さ
+    fn len_of(&self, k: usize) -> usize {
+        let depth = usize::max_value().count_ones() - 1 - k.leading_zeros();
+        self.size >> depth
+    }
+    /// `data[k]` に作用させ、子への作用を `lazy[k]` に溜める
+    fn apply(&mut self, k: usize, op: &T::Operator) {
+        let len = self.len_of(k);
+        T::operate_with_len(&mut self.data[k], op, len);
+        if k < self.size {
+            self.lazy[k] = Some(match self.lazy[k].take() {
+                Some(lazy) => T::composition(&lazy, op),
+                None => op.clone(),
+            });
+        }
+    }
+    fn push(&mut self, k: usize) {
+        if let Some(lazy) = self.lazy[k].take() {
+            self.apply(2 * k, &lazy);
+            self.apply(2 * k + 1, &lazy);
         }
     }
 }
 
+fn range_from(len: usize, range: impl RangeBounds<usize>) -> Range<usize> {
+    use Bound::*;
+    let start = match range.start_bound() {
+        Included(&start) => start,
+        Excluded(&start) => start + 1,
+        Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Excluded(&end) => end,
+        Included(&end) => end + 1,
+        Unbounded => len,
+    };
+    assert!(start <= end, "invalid range: {}..{}", start, end);
+    assert!(end <= len, "index out of bounds: {}/{}", end, len);
+    Range { start, end }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::boxed::BoxedLazySegTree;
+    use crate::test_util::xorshift;
+    use crate::types::SumAdd;
+
+    /// 区間 affine 変換、区間和 (mod 998244353)
+    pub enum AffineSum {}
+    impl Type for AffineSum {
+        type Item = u64;
+        type Operator = (u64, u64);
+        fn id() -> u64 {
+            0
+        }
+        fn prod(a: &u64, b: &u64) -> u64 {
+            (a + b) % 998_244_353
+        }
+        fn composition(&(a, b): &(u64, u64), &(c, d): &(u64, u64)) -> (u64, u64) {
+            (a * c % 998_244_353, (b * c + d) % 998_244_353)
+        }
+        fn operate_with_len(val: &mut u64, &(a, b): &(u64, u64), len: usize) {
+            *val = (a * *val + b * len as u64) % 998_244_353;
+        }
+    }
 
     #[test]
     fn test() {
-        pub enum SumAdd {}
-        impl Type for SumAdd {
-            type Item = u32;
-            type Operator = u32;
-            fn id() -> u32 {
-                0
-            }
-            fn prod(a: &u32, b: &u32) -> u32 {
-                a + b
-            }
-            fn composition(a: &u32, b: &u32) -> u32 {
-                a + b
-            }
-            fn operate_with_len(val: &mut u32, op: &u32, len: usize) {
-                *val += op * len as u32;
-            }
-        }
-        let mut lst = LazySegTree::<SumAdd>::new(4);
+        let mut lst = LazySegTree::<SumAdd<u32>>::new(4);
         lst.operate(..3, &2);
         dbg!((0..4).map(|i| *lst.get(i)).collect::<Vec<_>>());
         dbg!((0..4).map(|i| *lst.get(i)).collect::<Vec<_>>());
         assert_eq!(lst.prod_range(..3), 6);
         assert_eq!(lst.get(0), &2);
     }
+
+    #[test]
+    fn test_random() {
+        let mut seed = 1;
+        for len in 0..20 {
+            let mut naive: Vec<u64> = (0..len).map(|_| xorshift(&mut seed) % 100).collect();
+            let mut lst = LazySegTree::<AffineSum>::from(&naive[..]);
+            let mut boxed = BoxedLazySegTree::<AffineSum>::from(&naive[..]);
+            for _ in 0..200 {
+                let l = xorshift(&mut seed) as usize % (len + 1);
+                let r = xorshift(&mut seed) as usize % (len + 1);
+                let (l, r) = (l.min(r), l.max(r));
+                let a = xorshift(&mut seed) % 998_244_353;
+                let b = xorshift(&mut seed) % 998_244_353;
+                match xorshift(&mut seed) % 4 {
+                    0 => {
+                        lst.operate(l..r, &(a, b));
+                        boxed.operate(l..r, &(a, b));
+                        for v in &mut naive[l..r] {
+                            *v = (a * *v + b) % 998_244_353;
+                        }
+                    }
+                    1 if l < len => {
+                        lst.set(l, a);
+                        boxed.set(l, a);
+                        naive[l] = a;
+                    }
+                    2 if l < len => {
+                        assert_eq!(lst.peek(l), naive[l]);
+                        assert_eq!(*lst.get(l), naive[l]);
+                        assert_eq!(*boxed.get(l), naive[l]);
+                    }
+                    _ => {
+                        let expected = naive[l..r].iter().sum::<u64>() % 998_244_353;
+                        assert_eq!(lst.peek_range(l..r), expected);
+                        assert_eq!(lst.prod_range(l..r), expected);
+                        assert_eq!(boxed.prod_range(l..r), expected);
+                    }
+                }
+            }
+            let expected = naive.iter().sum::<u64>() % 998_244_353;
+            assert_eq!(*lst.prod(), expected);
+            assert_eq!(*boxed.prod(), expected);
+        }
+    }
+
+    #[test]
+    fn test_peek_shared() {
+        let mut lst = LazySegTree::<SumAdd<u32>>::from(&[1, 2, 3, 4, 5][..]);
+        lst.operate(1..4, &10);
+        lst.operate(..2, &100);
+        let lst = &lst;
+        let sums: Vec<_> = (0..=5).map(|r| lst.peek_range(..r)).collect();
+        assert_eq!(sums, vec![0, 101, 213, 226, 240, 245]);
+        assert_eq!(
+            (0..5).map(|i| lst.peek(i)).collect::<Vec<_>>(),
+            vec![101, 112, 13, 14, 5]
+        );
+    }
+
+    #[test]
+    fn test_max_right_min_left() {
+        let mut seed = 3;
+        for len in 0..20 {
+            let mut naive = vec![0u32; len];
+            let mut lst = LazySegTree::<SumAdd<u32>>::new(len);
+            for _ in 0..50 {
+                let l = xorshift(&mut seed) as usize % (len + 1);
+                let r = xorshift(&mut seed) as usize % (len + 1);
+                let (l, r) = (l.min(r), l.max(r));
+                let x = xorshift(&mut seed) as u32 % 10;
+                lst.operate(l..r, &x);
+                for v in &mut naive[l..r] {
+                    *v += x;
+                }
+                let x = xorshift(&mut seed) as u32 % 100;
+                for l in 0..=len {
+                    let expected = (l..=len)
+                        .rev()
+                        .find(|&r| naive[l..r].iter().sum::<u32>() <= x)
+                        .unwrap();
+                    assert_eq!(lst.max_right(l, |&s| s <= x), expected);
+                }
+                for r in 0..=len {
+                    let expected = (0..=r)
+                        .find(|&l| naive[l..r].iter().sum::<u32>() <= x)
+                        .unwrap();
+                    assert_eq!(lst.min_left(r, |&s| s <= x), expected);
+                }
+            }
+        }
+    }
+
+    /// `cargo test --release -- --ignored --nocapture` で実行する
+    #[test]
+    #[ignore]
+    fn bench() {
+        use std::time::Instant;
+        const N: usize = 200_000;
+        let mut seed = 2;
+        let queries: Vec<_> = (0..N)
+            .map(|_| {
+                let l = xorshift(&mut seed) as usize % N;
+                let r = xorshift(&mut seed) as usize % N;
+                (l.min(r), l.max(r) + 1, xorshift(&mut seed) % 998_244_353)
+            })
+            .collect();
+        let init = vec![1; N];
+
+        let instant = Instant::now();
+        let mut lst = LazySegTree::<AffineSum>::from(&init[..]);
+        let mut sum_array = 0;
+        for &(l, r, x) in &queries {
+            lst.operate(l..r, &(x, x));
+            sum_array += lst.prod_range(l..r);
+        }
+        let time_array = instant.elapsed();
+
+        let instant = Instant::now();
+        let mut boxed = BoxedLazySegTree::<AffineSum>::from(&init[..]);
+        let mut sum_boxed = 0;
+        for &(l, r, x) in &queries {
+            boxed.operate(l..r, &(x, x));
+            sum_boxed += boxed.prod_range(l..r);
+        }
+        let time_boxed = instant.elapsed();
+
+        assert_eq!(sum_array, sum_boxed);
+        println!("LazySegTree: {:?}", time_array);
+        println!("BoxedLazySegTree: {:?}", time_boxed);
+    }
 }
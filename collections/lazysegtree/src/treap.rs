@@ -0,0 +1,412 @@
+use crate::{range_from, Type};
+use std::{
+    cell::Cell,
+    cmp::Ordering,
+    ops::{Range, RangeBounds},
+};
+
+/// 添字をキーとする treap
+/// 挿入、削除、分割、連結、区間反転、区間作用、区間積をそれぞれ期待 O(log n) で処理する
+pub struct ImplicitTreap<T: Type> {
+    root: Tree<T>,
+}
+
+type Tree<T> = Option<Box<Node<T>>>;
+
+/// `val`, `prod`, `rev_prod` には `lazy` が作用済みで、`rev` も反映済み
+/// `lazy` と `rev` は子に伝えていないもの
+struct Node<T: Type> {
+    val: T::Item,
+    prod: T::Item,
+    /// 部分木を逆順に並べたものの積
+    rev_prod: T::Item,
+    len: usize,
+    priority: u64,
+    lazy: Option<T::Operator>,
+    rev: bool,
+    left: Tree<T>,
+    right: Tree<T>,
+}
+
+impl<T: Type> Default for ImplicitTreap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Type> From<&[T::Item]> for ImplicitTreap<T> {
+    fn from(slice: &[T::Item]) -> Self {
+        let mut treap = Self::new();
+        for val in slice {
+            let node = node(val.clone());
+            treap.root = merge(treap.root.take(), node);
+        }
+        treap
+    }
+}
+
+impl<T: Type> ImplicitTreap<T> {
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+    pub fn len(&self) -> usize {
+        len(&self.root)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+    /// 全体の積
+    pub fn prod(&self) -> T::Item {
+        self.root
+            .as_ref()
+            .map_or_else(T::id, |root| root.prod.clone())
+    }
+    pub fn prod_range(&mut self, range: impl RangeBounds<usize>) -> T::Item {
+        let Range { start, end } = range_from(self.len(), range);
+        let (left, mid, right) = split3(self.root.take(), start, end);
+        let ret = mid.as_ref().map_or_else(T::id, |mid| mid.prod.clone());
+        self.root = merge(merge(left, mid), right);
+        ret
+    }
+    pub fn get(&mut self, i: usize) -> &T::Item {
+        assert!(i < self.len(), "index out: {}/{}", i, self.len());
+        let mut node = self.root.as_mut().unwrap();
+        let mut i = i;
+        loop {
+            push(node);
+            let left = len(&node.left);
+            match i.cmp(&left) {
+                Ordering::Less => node = node.left.as_mut().unwrap(),
+                Ordering::Equal => return &node.val,
+                Ordering::Greater => {
+                    i -= left + 1;
+                    node = node.right.as_mut().unwrap();
+                }
+            }
+        }
+    }
+    pub fn set(&mut self, i: usize, val: T::Item) {
+        assert!(i < self.len(), "index out: {}/{}", i, self.len());
+        let (left, mid, right) = split3(self.root.take(), i, i + 1);
+        let mut mid = mid.unwrap();
+        mid.val = val.clone();
+        mid.prod = val.clone();
+        mid.rev_prod = val;
+        self.root = merge(merge(left, Some(mid)), right);
+    }
+    /// `i` 番目が `val` になるように挿入する
+    pub fn insert(&mut self, i: usize, val: T::Item) {
+        assert!(i <= self.len(), "index out: {}/{}", i, self.len());
+        let node = node(val);
+        let (left, right) = split(self.root.take(), i);
+        self.root = merge(merge(left, node), right);
+    }
+    pub fn remove(&mut self, i: usize) -> T::Item {
+        assert!(i < self.len(), "index out: {}/{}", i, self.len());
+        let (left, mid, right) = split3(self.root.take(), i, i + 1);
+        self.root = merge(left, right);
+        mid.unwrap().val
+    }
+    pub fn push_back(&mut self, val: T::Item) {
+        let node = node(val);
+        self.root = merge(self.root.take(), node);
+    }
+    /// `(self[..i], self[i..])`
+    pub fn split_at(self, i: usize) -> (Self, Self) {
+        assert!(i <= self.len(), "index out: {}/{}", i, self.len());
+        let (left, right) = split(self.root, i);
+        (Self { root: left }, Self { root: right })
+    }
+    /// `self` のあとに `other` を連結する
+    pub fn concat(self, other: Self) -> Self {
+        Self {
+            root: merge(self.root, other.root),
+        }
+    }
+    pub fn reverse(&mut self, range: impl RangeBounds<usize>) {
+        let Range { start, end } = range_from(self.len(), range);
+        let (left, mut mid, right) = split3(self.root.take(), start, end);
+        if let Some(mid) = mid.as_mut() {
+            reverse(mid);
+        }
+        self.root = merge(merge(left, mid), right);
+    }
+    pub fn operate(&mut self, range: impl RangeBounds<usize>, op: &T::Operator) {
+        let Range { start, end } = range_from(self.len(), range);
+        let (left, mut mid, right) = split3(self.root.take(), start, end);
+        if let Some(mid) = mid.as_mut() {
+            apply(mid, op);
+        }
+        self.root = merge(merge(left, mid), right);
+    }
+    /// 先頭から順に列挙する
+    pub fn values(&mut self) -> Vec<T::Item> {
+        fn dfs<T: Type>(node: &mut Tree<T>, vec: &mut Vec<T::Item>) {
+            if let Some(node) = node {
+                push(node);
+                dfs(&mut node.left, vec);
+                vec.push(node.val.clone());
+                dfs(&mut node.right, vec);
+            }
+        }
+        let mut vec = Vec::with_capacity(self.len());
+        dfs(&mut self.root, &mut vec);
+        vec
+    }
+}
+
+/// 優先度の乱数列はすべての treap で共有する
+/// treap ごとに持つと、別々に作った treap を連結したときに優先度が一致して木が偏る
+fn priority() -> u64 {
+    thread_local!(static SEED: Cell<u64> = Cell::new(88_172_645_463_325_252));
+    SEED.with(|seed| {
+        let mut x = seed.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        seed.set(x);
+        x
+    })
+}
+
+fn node<T: Type>(val: T::Item) -> Tree<T> {
+    Some(Box::new(Node {
+        prod: val.clone(),
+        rev_prod: val.clone(),
+        val,
+        len: 1,
+        priority: priority(),
+        lazy: None,
+        rev: false,
+        left: None,
+        right: None,
+    }))
+}
+
+fn len<T: Type>(tree: &Tree<T>) -> usize {
+    tree.as_ref().map_or(0, |node| node.len)
+}
+
+fn update<T: Type>(node: &mut Node<T>) {
+    node.len = 1 + len(&node.left) + len(&node.right);
+    node.prod = node.val.clone();
+    node.rev_prod = node.val.clone();
+    if let Some(left) = &node.left {
+        node.prod = T::prod(&left.prod, &node.prod);
+        node.rev_prod = T::prod(&node.rev_prod, &left.rev_prod);
+    }
+    if let Some(right) = &node.right {
+        node.prod = T::prod(&node.prod, &right.prod);
+        node.rev_prod = T::prod(&right.rev_prod, &node.rev_prod);
+    }
+}
+
+fn apply<T: Type>(node: &mut Node<T>, op: &T::Operator) {
+    T::operate(&mut node.val, op);
+    T::operate_with_len(&mut node.prod, op, node.len);
+    T::operate_with_len(&mut node.rev_prod, op, node.len);
+    node.lazy = Some(match node.lazy.take() {
+        Some(lazy) => T::composition(&lazy, op),
+        None => op.clone(),
+    });
+}
+
+fn reverse<T: Type>(node: &mut Node<T>) {
+    std::mem::swap(&mut node.left, &mut node.right);
+    std::mem::swap(&mut node.prod, &mut node.rev_prod);
+    node.rev ^= true;
+}
+
+fn push<T: Type>(node: &mut Node<T>) {
+    if let Some(lazy) = node.lazy.take() {
+        if let Some(left) = node.left.as_mut() {
+            apply(left, &lazy);
+        }
+        if let Some(right) = node.right.as_mut() {
+            apply(right, &lazy);
+        }
+    }
+    if node.rev {
+        node.rev = false;
+        if let Some(left) = node.left.as_mut() {
+            reverse(left);
+        }
+        if let Some(right) = node.right.as_mut() {
+            reverse(right);
+        }
+    }
+}
+
+fn merge<T: Type>(a: Tree<T>, b: Tree<T>) -> Tree<T> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a.priority > b.priority {
+                push(&mut a);
+                a.right = merge(a.right.take(), Some(b));
+                update(&mut a);
+                Some(a)
+            } else {
+                push(&mut b);
+                b.left = merge(Some(a), b.left.take());
+                update(&mut b);
+                Some(b)
+            }
+        }
+    }
+}
+
+/// `(tree[..k], tree[k..])`
+fn split<T: Type>(tree: Tree<T>, k: usize) -> (Tree<T>, Tree<T>) {
+    match tree {
+        None => (None, None),
+        Some(mut node) => {
+            push(&mut node);
+            let left = len(&node.left);
+            if k <= left {
+                let (a, b) = split(node.left.take(), k);
+                node.left = b;
+                update(&mut node);
+                (a, Some(node))
+            } else {
+                let (a, b) = split(node.right.take(), k - left - 1);
+                node.right = a;
+                update(&mut node);
+                (Some(node), b)
+            }
+        }
+    }
+}
+
+/// `(tree[..start], tree[start..end], tree[end..])`
+fn split3<T: Type>(tree: Tree<T>, start: usize, end: usize) -> (Tree<T>, Tree<T>, Tree<T>) {
+    let (left, rest) = split(tree, start);
+    let (mid, right) = split(rest, end - start);
+    (left, mid, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+    use crate::types::SumAdd;
+
+    #[test]
+    fn test_random() {
+        let mut seed = 1;
+        let mut naive: Vec<i64> = (0..10).collect();
+        let mut treap = ImplicitTreap::<SumAdd<i64>>::from(&naive[..]);
+        for _ in 0..2000 {
+            let len = naive.len();
+            let l = xorshift(&mut seed) as usize % (len + 1);
+            let r = xorshift(&mut seed) as usize % (len + 1);
+            let (l, r) = (l.min(r), l.max(r));
+            let x = (xorshift(&mut seed) % 100) as i64 - 50;
+            match xorshift(&mut seed) % 8 {
+                0 => {
+                    treap.insert(l, x);
+                    naive.insert(l, x);
+                }
+                1 if l < len => assert_eq!(treap.remove(l), naive.remove(l)),
+                2 => {
+                    treap.reverse(l..r);
+                    naive[l..r].reverse();
+                }
+                3 => {
+                    treap.operate(l..r, &x);
+                    naive[l..r].iter_mut().for_each(|v| *v += x);
+                }
+                4 if l < len => {
+                    treap.set(l, x);
+                    naive[l] = x;
+                }
+                5 if l < len => assert_eq!(*treap.get(l), naive[l]),
+                6 => {
+                    let (a, b) = treap.split_at(l);
+                    assert_eq!(a.len(), l);
+                    assert_eq!(a.prod(), naive[..l].iter().sum());
+                    assert_eq!(b.prod(), naive[l..].iter().sum());
+                    treap = a.concat(b);
+                }
+                _ => assert_eq!(treap.prod_range(l..r), naive[l..r].iter().sum()),
+            }
+            assert_eq!(treap.len(), naive.len());
+        }
+        assert_eq!(treap.values(), naive);
+    }
+
+    #[test]
+    fn test_split_concat() {
+        let treap = ImplicitTreap::<SumAdd<i32>>::from(&[1, 2, 3, 4, 5][..]);
+        let (mut a, mut b) = treap.split_at(2);
+        a.reverse(..);
+        b.operate(1.., &10);
+        let mut c = b.concat(a);
+        assert_eq!(c.values(), vec![3, 14, 15, 2, 1]);
+        c.push_back(6);
+        assert_eq!(c.prod_range(1..=3), 31);
+        assert!(ImplicitTreap::<SumAdd<i32>>::new().is_empty());
+    }
+
+    #[test]
+    fn test_concat_many() {
+        fn depth<T: Type>(tree: &Tree<T>) -> usize {
+            tree.as_ref()
+                .map_or(0, |node| 1 + depth(&node.left).max(depth(&node.right)))
+        }
+        let n = 20_000;
+        let mut treap = ImplicitTreap::<SumAdd<i64>>::new();
+        for i in 0..n {
+            treap = treap.concat(ImplicitTreap::from(&[i][..]));
+        }
+        assert!(depth(&treap.root) < 100);
+        assert_eq!(treap.prod_range(..n as usize / 2), (0..n / 2).sum());
+    }
+
+    /// 一次関数の合成、可換でない
+    enum Affine {}
+    impl Type for Affine {
+        type Item = (u64, u64);
+        type Operator = ();
+        fn id() -> (u64, u64) {
+            (1, 0)
+        }
+        fn prod(&(a, b): &(u64, u64), &(c, d): &(u64, u64)) -> (u64, u64) {
+            (a * c % 998_244_353, (b * c + d) % 998_244_353)
+        }
+        fn composition(_: &(), _: &()) {}
+        fn operate_with_len(_: &mut (u64, u64), _: &(), _: usize) {}
+    }
+
+    #[test]
+    fn test_reverse_noncommutative() {
+        let mut seed = 3;
+        let mut naive: Vec<(u64, u64)> = vec![];
+        let mut treap = ImplicitTreap::<Affine>::new();
+        for _ in 0..1000 {
+            let len = naive.len();
+            let l = xorshift(&mut seed) as usize % (len + 1);
+            let r = xorshift(&mut seed) as usize % (len + 1);
+            let (l, r) = (l.min(r), l.max(r));
+            match xorshift(&mut seed) % 3 {
+                0 => {
+                    let f = (xorshift(&mut seed) % 10, xorshift(&mut seed) % 10);
+                    treap.insert(l, f);
+                    naive.insert(l, f);
+                }
+                1 => {
+                    treap.reverse(l..r);
+                    naive[l..r].reverse();
+                }
+                _ => {
+                    let expected = naive[l..r]
+                        .iter()
+                        .fold(Affine::id(), |acc, f| Affine::prod(&acc, f));
+                    assert_eq!(treap.prod_range(l..r), expected);
+                }
+            }
+        }
+        assert_eq!(treap.values(), naive);
+    }
+}
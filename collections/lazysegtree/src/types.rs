@@ -0,0 +1,267 @@
+//! 典型的な `Type` の実装
+//! 名前は `{区間取得}{区間作用}` の順
+
+use crate::Type;
+use std::marker::PhantomData;
+
+/// 区間加算、区間和
+pub struct SumAdd<T>(PhantomData<T>);
+/// 区間加算、区間最小値
+/// `T::max_value()` は単位元 (+∞) として扱い、加算しない
+pub struct MinAdd<T>(PhantomData<T>);
+/// 区間代入、区間最小値
+pub struct MinAssign<T>(PhantomData<T>);
+/// 区間代入、区間和
+pub struct SumAssign<T>(PhantomData<T>);
+/// 区間 affine 変換、区間和
+/// `(a, b)` は `x -> a * x + b` を表し、`composition(f, g)` は `f` のあと `g` を適用する
+pub struct SumAffine<T>(PhantomData<T>);
+/// `0`/`1` の列の区間反転、`1` の個数
+pub enum CountFlip {}
+/// 区間 xor、区間和
+/// 要素は `SumXor::item(x)` で作り、和は `SumXor::sum(&item)` で得る
+pub enum SumXor {}
+
+macro_rules! impl_types {
+    ($($t:ty),*) => {$(
+        impl Type for SumAdd<$t> {
+            type Item = $t;
+            type Operator = $t;
+            fn id() -> $t {
+                0
+            }
+            fn prod(a: &$t, b: &$t) -> $t {
+                a + b
+            }
+            fn composition(a: &$t, b: &$t) -> $t {
+                a + b
+            }
+            fn operate_with_len(val: &mut $t, op: &$t, len: usize) {
+                *val += op * len as $t;
+            }
+        }
+        impl Type for MinAdd<$t> {
+            type Item = $t;
+            type Operator = $t;
+            fn id() -> $t {
+                <$t>::max_value()
+            }
+            fn prod(a: &$t, b: &$t) -> $t {
+                *a.min(b)
+            }
+            fn composition(a: &$t, b: &$t) -> $t {
+                a + b
+            }
+            fn operate_with_len(val: &mut $t, op: &$t, _: usize) {
+                if *val != <$t>::max_value() {
+                    *val += op;
+                }
+            }
+        }
+        impl Type for MinAssign<$t> {
+            type Item = $t;
+            type Operator = $t;
+            fn id() -> $t {
+                <$t>::max_value()
+            }
+            fn prod(a: &$t, b: &$t) -> $t {
+                *a.min(b)
+            }
+            fn composition(_: &$t, b: &$t) -> $t {
+                *b
+            }
+            fn operate_with_len(val: &mut $t, op: &$t, _: usize) {
+                *val = *op;
+            }
+        }
+        impl Type for SumAssign<$t> {
+            type Item = $t;
+            type Operator = $t;
+            fn id() -> $t {
+                0
+            }
+            fn prod(a: &$t, b: &$t) -> $t {
+                a + b
+            }
+            fn composition(_: &$t, b: &$t) -> $t {
+                *b
+            }
+            fn operate_with_len(val: &mut $t, op: &$t, len: usize) {
+                *val = op * len as $t;
+            }
+        }
+        impl Type for SumAffine<$t> {
+            type Item = $t;
+            type Operator = ($t, $t);
+            fn id() -> $t {
+                0
+            }
+            fn prod(a: &$t, b: &$t) -> $t {
+                a + b
+            }
+            fn composition(&(a, b): &($t, $t), &(c, d): &($t, $t)) -> ($t, $t) {
+                (a * c, b * c + d)
+            }
+            fn operate_with_len(val: &mut $t, &(a, b): &($t, $t), len: usize) {
+                *val = a * *val + b * len as $t;
+            }
+        }
+    )*};
+}
+impl_types!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Type for CountFlip {
+    type Item = usize;
+    type Operator = bool;
+    fn id() -> usize {
+        0
+    }
+    fn prod(a: &usize, b: &usize) -> usize {
+        a + b
+    }
+    fn composition(a: &bool, b: &bool) -> bool {
+        a ^ b
+    }
+    fn operate_with_len(val: &mut usize, &op: &bool, len: usize) {
+        if op {
+            *val = len - *val;
+        }
+    }
+}
+
+impl SumXor {
+    /// `x` のみからなる列
+    pub fn item(x: u32) -> [usize; 32] {
+        let mut ret = [0; 32];
+        for (i, c) in ret.iter_mut().enumerate() {
+            *c = (x >> i & 1) as usize;
+        }
+        ret
+    }
+    pub fn sum(item: &[usize; 32]) -> u64 {
+        item.iter().enumerate().map(|(i, &c)| (c as u64) << i).sum()
+    }
+}
+
+impl Type for SumXor {
+    /// 各ビットが立っている要素の個数
+    type Item = [usize; 32];
+    type Operator = u32;
+    fn id() -> [usize; 32] {
+        [0; 32]
+    }
+    fn prod(a: &[usize; 32], b: &[usize; 32]) -> [usize; 32] {
+        let mut ret = *a;
+        for (r, b) in ret.iter_mut().zip(b) {
+            *r += b;
+        }
+        ret
+    }
+    fn composition(a: &u32, b: &u32) -> u32 {
+        a ^ b
+    }
+    fn operate_with_len(val: &mut [usize; 32], op: &u32, len: usize) {
+        for (i, c) in val.iter_mut().enumerate() {
+            if op >> i & 1 == 1 {
+                *c = len - *c;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+    use crate::LazySegTree;
+
+    /// ランダムな区間作用と区間取得を愚直と比較する
+    fn check<T, V, O, N>(seed: u64, val: V, op: O, naive_operate: N)
+    where
+        T: Type,
+        T::Item: PartialEq + std::fmt::Debug,
+        V: Fn(u64) -> T::Item,
+        O: Fn(u64) -> T::Operator,
+        N: Fn(&mut T::Item, &T::Operator),
+    {
+        let mut seed = seed;
+        let len = 13;
+        let mut naive: Vec<_> = (0..len).map(|_| val(xorshift(&mut seed))).collect();
+        let mut lst = LazySegTree::<T>::from(&naive[..]);
+        for _ in 0..300 {
+            let l = xorshift(&mut seed) as usize % (len + 1);
+            let r = xorshift(&mut seed) as usize % (len + 1);
+            let (l, r) = (l.min(r), l.max(r));
+            if xorshift(&mut seed) % 2 == 0 {
+                let op = op(xorshift(&mut seed));
+                lst.operate(l..r, &op);
+                for v in &mut naive[l..r] {
+                    naive_operate(v, &op);
+                }
+            } else {
+                let expected = naive[l..r].iter().fold(T::id(), |acc, v| T::prod(&acc, v));
+                assert_eq!(lst.prod_range(l..r), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_types() {
+        check::<SumAdd<i64>, _, _, _>(
+            1,
+            |x| x as i64 % 100,
+            |x| x as i64 % 100 - 50,
+            |v, op| *v += op,
+        );
+        check::<MinAdd<i64>, _, _, _>(
+            2,
+            |x| x as i64 % 100,
+            |x| x as i64 % 100 - 50,
+            |v, op| *v += op,
+        );
+        check::<MinAssign<i64>, _, _, _>(
+            3,
+            |x| x as i64 % 100,
+            |x| x as i64 % 100,
+            |v, op| *v = *op,
+        );
+        check::<SumAssign<i64>, _, _, _>(
+            4,
+            |x| x as i64 % 100,
+            |x| x as i64 % 100,
+            |v, op| *v = *op,
+        );
+        check::<SumAffine<u64>, _, _, _>(
+            5,
+            |x| x % 100,
+            |x| (x % 2, x / 2 % 5),
+            |v, &(a, b)| *v = a * *v + b,
+        );
+        check::<CountFlip, _, _, _>(
+            6,
+            |x| x as usize % 2,
+            |x| x % 2 == 0,
+            |v, &op| {
+                if op {
+                    *v = 1 - *v
+                }
+            },
+        );
+        check::<SumXor, _, _, _>(
+            7,
+            |x| SumXor::item(x as u32),
+            |x| (x >> 32) as u32,
+            |v, &op| *v = SumXor::item(SumXor::sum(v) as u32 ^ op),
+        );
+    }
+
+    #[test]
+    fn test_sum_xor() {
+        let vec: Vec<_> = [1, 2, 3, 4].iter().map(|&x| SumXor::item(x)).collect();
+        let mut lst = LazySegTree::<SumXor>::from(&vec[..]);
+        lst.operate(1..3, &5);
+        // [1, 7, 6, 4]
+        assert_eq!(SumXor::sum(&lst.prod_range(..)), 18);
+        assert_eq!(SumXor::sum(&lst.prod_range(1..=2)), 13);
+    }
+}
@@ -0,0 +1,351 @@
+use std::ops::{Bound, Range, RangeBounds};
+
+const INF: i64 = std::i64::MAX;
+const NEG_INF: i64 = std::i64::MIN;
+
+/// Segment Tree Beats
+/// 区間 chmin, chmax, 加算と区間和, 最小値, 最大値を、ならし O(log^2 n) で処理する
+pub struct SegTreeBeats {
+    len: usize,
+    nodes: Vec<Node>,
+}
+
+#[derive(Clone)]
+struct Node {
+    len: usize,
+    sum: i64,
+    max: i64,
+    /// 真に 2 番目に大きい値、なければ `NEG_INF`
+    max2: i64,
+    max_cnt: usize,
+    min: i64,
+    /// 真に 2 番目に小さい値、なければ `INF`
+    min2: i64,
+    min_cnt: usize,
+    /// 子に伝えていない加算
+    add: i64,
+}
+
+impl Node {
+    fn leaf(val: i64) -> Self {
+        Self {
+            len: 1,
+            sum: val,
+            max: val,
+            max2: NEG_INF,
+            max_cnt: 1,
+            min: val,
+            min2: INF,
+            min_cnt: 1,
+            add: 0,
+        }
+    }
+    fn merge(a: &Self, b: &Self) -> Self {
+        use std::cmp::Ordering::*;
+        let (max, max2, max_cnt) = match a.max.cmp(&b.max) {
+            Greater => (a.max, a.max2.max(b.max), a.max_cnt),
+            Less => (b.max, a.max.max(b.max2), b.max_cnt),
+            Equal => (a.max, a.max2.max(b.max2), a.max_cnt + b.max_cnt),
+        };
+        let (min, min2, min_cnt) = match a.min.cmp(&b.min) {
+            Less => (a.min, a.min2.min(b.min), a.min_cnt),
+            Greater => (b.min, a.min.min(b.min2), b.min_cnt),
+            Equal => (a.min, a.min2.min(b.min2), a.min_cnt + b.min_cnt),
+        };
+        Self {
+            len: a.len + b.len,
+            sum: a.sum + b.sum,
+            max,
+            max2,
+            max_cnt,
+            min,
+            min2,
+            min_cnt,
+            add: 0,
+        }
+    }
+    fn add(&mut self, x: i64) {
+        self.sum += x * self.len as i64;
+        self.max += x;
+        if self.max2 != NEG_INF {
+            self.max2 += x;
+        }
+        self.min += x;
+        if self.min2 != INF {
+            self.min2 += x;
+        }
+        self.add += x;
+    }
+    /// `max2 < x < max` である必要がある
+    fn chmin(&mut self, x: i64) {
+        self.sum -= (self.max - x) * self.max_cnt as i64;
+        if self.max == self.min {
+            self.min = x;
+        } else if self.max == self.min2 {
+            self.min2 = x;
+        }
+        self.max = x;
+    }
+    /// `min < x < min2` である必要がある
+    fn chmax(&mut self, x: i64) {
+        self.sum += (x - self.min) * self.min_cnt as i64;
+        if self.min == self.max {
+            self.max = x;
+        } else if self.min == self.max2 {
+            self.max2 = x;
+        }
+        self.min = x;
+    }
+}
+
+impl From<&[i64]> for SegTreeBeats {
+    fn from(slice: &[i64]) -> Self {
+        let mut seg = Self {
+            len: slice.len(),
+            nodes: vec![Node::leaf(0); slice.len().max(1) * 4],
+        };
+        if !slice.is_empty() {
+            seg.build(1, slice);
+        }
+        seg
+    }
+}
+
+impl SegTreeBeats {
+    pub fn new(n: usize) -> Self {
+        Self::from(&vec![0; n][..])
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// `range` の各要素 `a` を `min(a, x)` にする
+    pub fn chmin(&mut self, range: impl RangeBounds<usize>, x: i64) {
+        let Range { start, end } = range_from(self.len, range);
+        if start < end {
+            self.chmin_rec(1, 0, self.len, start, end, x);
+        }
+    }
+    /// `range` の各要素 `a` を `max(a, x)` にする
+    pub fn chmax(&mut self, range: impl RangeBounds<usize>, x: i64) {
+        let Range { start, end } = range_from(self.len, range);
+        if start < end {
+            self.chmax_rec(1, 0, self.len, start, end, x);
+        }
+    }
+    /// `range` の各要素に `x` を加える
+    pub fn add(&mut self, range: impl RangeBounds<usize>, x: i64) {
+        let Range { start, end } = range_from(self.len, range);
+        if start < end {
+            self.add_rec(1, 0, self.len, start, end, x);
+        }
+    }
+    /// 空なら `0`
+    pub fn sum(&mut self, range: impl RangeBounds<usize>) -> i64 {
+        self.fold(range, 0, |node| node.sum, |a, b| a + b)
+    }
+    /// 空なら `i64::MAX`
+    pub fn min(&mut self, range: impl RangeBounds<usize>) -> i64 {
+        self.fold(range, INF, |node| node.min, i64::min)
+    }
+    /// 空なら `i64::MIN`
+    pub fn max(&mut self, range: impl RangeBounds<usize>) -> i64 {
+        self.fold(range, NEG_INF, |node| node.max, i64::max)
+    }
+    pub fn get(&mut self, i: usize) -> i64 {
+        assert!(i < self.len, "index out: {}/{}", i, self.len);
+        self.sum(i..=i)
+    }
+    fn fold(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        id: i64,
+        f: impl Fn(&Node) -> i64 + Copy,
+        prod: impl Fn(i64, i64) -> i64 + Copy,
+    ) -> i64 {
+        let Range { start, end } = range_from(self.len, range);
+        if start == end {
+            return id;
+        }
+        self.fold_rec(1, 0, self.len, start, end, f, prod)
+    }
+    fn build(&mut self, k: usize, slice: &[i64]) {
+        if slice.len() == 1 {
+            self.nodes[k] = Node::leaf(slice[0]);
+            return;
+        }
+        let mid = slice.len() / 2;
+        self.build(2 * k, &slice[..mid]);
+        self.build(2 * k + 1, &slice[mid..]);
+        self.update(k);
+    }
+    fn update(&mut self, k: usize) {
+        self.nodes[k] = Node::merge(&self.nodes[2 * k], &self.nodes[2 * k + 1]);
+    }
+    fn push(&mut self, k: usize) {
+        let Node { add, max, min, .. } = self.nodes[k];
+        for c in 2 * k..2 * k + 2 {
+            let child = &mut self.nodes[c];
+            if add != 0 {
+                child.add(add);
+            }
+            if max < child.max {
+                child.chmin(max);
+            }
+            if child.min < min {
+                child.chmax(min);
+            }
+        }
+        self.nodes[k].add = 0;
+    }
+    fn chmin_rec(&mut self, k: usize, l: usize, r: usize, start: usize, end: usize, x: i64) {
+        if end <= l || r <= start || self.nodes[k].max <= x {
+            return;
+        }
+        if start <= l && r <= end && self.nodes[k].max2 < x {
+            self.nodes[k].chmin(x);
+            return;
+        }
+        self.push(k);
+        let mid = (l + r) / 2;
+        self.chmin_rec(2 * k, l, mid, start, end, x);
+        self.chmin_rec(2 * k + 1, mid, r, start, end, x);
+        self.update(k);
+    }
+    fn chmax_rec(&mut self, k: usize, l: usize, r: usize, start: usize, end: usize, x: i64) {
+        if end <= l || r <= start || x <= self.nodes[k].min {
+            return;
+        }
+        if start <= l && r <= end && x < self.nodes[k].min2 {
+            self.nodes[k].chmax(x);
+            return;
+        }
+        self.push(k);
+        let mid = (l + r) / 2;
+        self.chmax_rec(2 * k, l, mid, start, end, x);
+        self.chmax_rec(2 * k + 1, mid, r, start, end, x);
+        self.update(k);
+    }
+    fn add_rec(&mut self, k: usize, l: usize, r: usize, start: usize, end: usize, x: i64) {
+        if end <= l || r <= start {
+            return;
+        }
+        if start <= l && r <= end {
+            self.nodes[k].add(x);
+            return;
+        }
+        self.push(k);
+        let mid = (l + r) / 2;
+        self.add_rec(2 * k, l, mid, start, end, x);
+        self.add_rec(2 * k + 1, mid, r, start, end, x);
+        self.update(k);
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn fold_rec(
+        &mut self,
+        k: usize,
+        l: usize,
+        r: usize,
+        start: usize,
+        end: usize,
+        f: impl Fn(&Node) -> i64 + Copy,
+        prod: impl Fn(i64, i64) -> i64 + Copy,
+    ) -> i64 {
+        if start <= l && r <= end {
+            return f(&self.nodes[k]);
+        }
+        self.push(k);
+        let mid = (l + r) / 2;
+        if end <= mid {
+            self.fold_rec(2 * k, l, mid, start, end, f, prod)
+        } else if mid <= start {
+            self.fold_rec(2 * k + 1, mid, r, start, end, f, prod)
+        } else {
+            prod(
+                self.fold_rec(2 * k, l, mid, start, end, f, prod),
+                self.fold_rec(2 * k + 1, mid, r, start, end, f, prod),
+            )
+        }
+    }
+}
+
+fn range_from(len: usize, range: impl RangeBounds<usize>) -> Range<usize> {
+    use Bound::*;
+    let start = match range.start_bound() {
+        Included(&start) => start,
+        Excluded(&start) => start + 1,
+        Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Excluded(&end) => end,
+        Included(&end) => end + 1,
+        Unbounded => len,
+    };
+    assert!(start <= end, "invalid range: {}..{}", start, end);
+    assert!(end <= len, "index out of bounds: {}/{}", end, len);
+    Range { start, end }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xorshift(seed: &mut u64) -> u64 {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        *seed
+    }
+
+    #[test]
+    fn test_random() {
+        let mut seed = 1;
+        for len in 1..30 {
+            let mut naive: Vec<i64> = (0..len)
+                .map(|_| (xorshift(&mut seed) % 200) as i64 - 100)
+                .collect();
+            let mut seg = SegTreeBeats::from(&naive[..]);
+            for _ in 0..300 {
+                let l = xorshift(&mut seed) as usize % (len + 1);
+                let r = xorshift(&mut seed) as usize % (len + 1);
+                let (l, r) = (l.min(r), l.max(r));
+                let x = (xorshift(&mut seed) % 200) as i64 - 100;
+                match xorshift(&mut seed) % 6 {
+                    0 => {
+                        seg.chmin(l..r, x);
+                        naive[l..r].iter_mut().for_each(|v| *v = x.min(*v));
+                    }
+                    1 => {
+                        seg.chmax(l..r, x);
+                        naive[l..r].iter_mut().for_each(|v| *v = x.max(*v));
+                    }
+                    2 => {
+                        seg.add(l..r, x / 10);
+                        naive[l..r].iter_mut().for_each(|v| *v += x / 10);
+                    }
+                    3 => assert_eq!(seg.sum(l..r), naive[l..r].iter().sum::<i64>()),
+                    4 => assert_eq!(
+                        seg.min(l..r),
+                        naive[l..r].iter().copied().min().unwrap_or(INF)
+                    ),
+                    _ => assert_eq!(
+                        seg.max(l..r),
+                        naive[l..r].iter().copied().max().unwrap_or(NEG_INF)
+                    ),
+                }
+            }
+            for (i, &v) in naive.iter().enumerate() {
+                assert_eq!(seg.get(i), v);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut seg = SegTreeBeats::new(0);
+        seg.chmin(.., 0);
+        assert_eq!(seg.sum(..), 0);
+    }
+}
@@ -1,3 +1,13 @@
+pub mod monoid;
+mod persistent;
+mod segtree2d;
+#[cfg(test)]
+mod test_util;
+
+pub use monoid::{ByMonoid, Monoid, Prod};
+pub use persistent::{MonoidPersistentSegTree, PersistentSegTree, Version};
+pub use segtree2d::{GetMut2D, MonoidSegTree2D, SegTree2D};
+
 use std::{
     ops::{Bound, Deref, DerefMut, Index, Range, RangeBounds},
     slice::SliceIndex,
@@ -12,6 +22,37 @@ pub struct SegTree<T, F> {
 
 impl<T: Clone, F: Fn(&T, &T) -> T> SegTree<T, F> {
     pub fn new(len: usize, id: T, prod: F) -> Self {
+        Self::new_with(len, id, prod)
+    }
+    /// O(n) で構築する
+    pub fn from_vec(vec: Vec<T>, id: T, prod: F) -> Self {
+        Self::from_vec_with(vec, id, prod)
+    }
+    /// O(n) で構築する
+    pub fn from_iter(iter: impl IntoIterator<Item = T>, id: T, prod: F) -> Self {
+        Self::from_vec_with(iter.into_iter().collect(), id, prod)
+    }
+}
+
+/// `Monoid` によって定まる `SegTree`
+pub type MonoidSegTree<M> = SegTree<<M as Monoid>::Item, ByMonoid<M>>;
+
+impl<M: Monoid> MonoidSegTree<M> {
+    pub fn new_monoid(len: usize) -> Self {
+        Self::new_with(len, M::id(), ByMonoid::default())
+    }
+    /// O(n) で構築する
+    pub fn from_vec_monoid(vec: Vec<M::Item>) -> Self {
+        Self::from_vec_with(vec, M::id(), ByMonoid::default())
+    }
+    /// O(n) で構築する
+    pub fn from_iter_monoid(iter: impl IntoIterator<Item = M::Item>) -> Self {
+        Self::from_vec_monoid(iter.into_iter().collect())
+    }
+}
+
+impl<T: Clone, F: Prod<T>> SegTree<T, F> {
+    fn new_with(len: usize, id: T, prod: F) -> Self {
         Self {
             data: vec![id.clone(); len * 2],
             len,
@@ -19,9 +60,26 @@ impl<T: Clone, F: Fn(&T, &T) -> T> SegTree<T, F> {
             prod,
         }
     }
+    fn from_vec_with(vec: Vec<T>, id: T, prod: F) -> Self {
+        let len = vec.len();
+        let mut data = Vec::with_capacity(len * 2);
+        data.extend(std::iter::repeat(id.clone()).take(len));
+        data.extend(vec);
+        let mut seg = Self {
+            data,
+            len,
+            id,
+            prod,
+        };
+        seg.rebuild();
+        seg
+    }
     pub fn len(&self) -> usize {
         self.len
     }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
     pub fn get(&self, i: usize) -> &T {
         &self.data[self.len + i]
     }
@@ -31,9 +89,18 @@ impl<T: Clone, F: Fn(&T, &T) -> T> SegTree<T, F> {
             seg: self,
         }
     }
+    /// 葉をまとめて変更し、最後に一度だけ O(n) で再構築する
+    pub fn set_all(&mut self) -> SetAll<T, F> {
+        SetAll { seg: self }
+    }
+    fn rebuild(&mut self) {
+        for i in (1..self.len).rev() {
+            self.update(i);
+        }
+    }
     fn update(&mut self, i: usize) {
         if i < self.len {
-            self.data[i] = (self.prod)(&self.data[2 * i], &self.data[2 * i + 1])
+            self.data[i] = self.prod.prod(&self.data[2 * i], &self.data[2 * i + 1])
         }
     }
     fn update_parent(&mut self, mut i: usize) {
@@ -51,17 +118,86 @@ impl<T: Clone, F: Fn(&T, &T) -> T> SegTree<T, F> {
         let mut right = self.id.clone();
         while start != end {
             if start & 1 != 0 {
-                left = (self.prod)(&left, &self.data[start]);
+                left = self.prod.prod(&left, &self.data[start]);
                 start += 1;
             }
             if end & 1 != 0 {
                 end -= 1;
-                right = (self.prod)(&self.data[end], &right);
+                right = self.prod.prod(&self.data[end], &right);
             }
             start >>= 1;
             end >>= 1;
         }
-        (self.prod)(&left, &right)
+        self.prod.prod(&left, &right)
+    }
+    /// `pred(&self.prod_range(l..r))` が `true` となる最大の `r` を返す
+    /// `pred` は単調であり、`pred(&id)` は `true` である必要がある
+    pub fn max_right(&self, l: usize, mut pred: impl FnMut(&T) -> bool) -> usize {
+        assert!(l <= self.len, "index out: {}/{}", l, self.len);
+        let mut acc = self.id.clone();
+        for i in self.nodes(l..self.len) {
+            let next = self.prod.prod(&acc, &self.data[i]);
+            if pred(&next) {
+                acc = next;
+                continue;
+            }
+            let mut i = i;
+            while i < self.len {
+                i *= 2;
+                let next = self.prod.prod(&acc, &self.data[i]);
+                if pred(&next) {
+                    acc = next;
+                    i += 1;
+                }
+            }
+            return i - self.len;
+        }
+        self.len
+    }
+    /// `pred(&self.prod_range(l..r))` が `true` となる最小の `l` を返す
+    /// `pred` は単調であり、`pred(&id)` は `true` である必要がある
+    pub fn min_left(&self, r: usize, mut pred: impl FnMut(&T) -> bool) -> usize {
+        assert!(r <= self.len, "index out: {}/{}", r, self.len);
+        let mut acc = self.id.clone();
+        for i in self.nodes(0..r).into_iter().rev() {
+            let next = self.prod.prod(&self.data[i], &acc);
+            if pred(&next) {
+                acc = next;
+                continue;
+            }
+            let mut i = i;
+            while i < self.len {
+                i = 2 * i + 1;
+                let next = self.prod.prod(&self.data[i], &acc);
+                if pred(&next) {
+                    acc = next;
+                    i -= 1;
+                }
+            }
+            return i + 1 - self.len;
+        }
+        0
+    }
+    /// `range` を覆う節点を左から順に返す
+    fn nodes(&self, Range { mut start, mut end }: Range<usize>) -> Vec<usize> {
+        start += self.len;
+        end += self.len;
+        let mut left = vec![];
+        let mut right = vec![];
+        while start != end {
+            if start & 1 != 0 {
+                left.push(start);
+                start += 1;
+            }
+            if end & 1 != 0 {
+                end -= 1;
+                right.push(end);
+            }
+            start >>= 1;
+            end >>= 1;
+        }
+        left.extend(right.into_iter().rev());
+        left
     }
 }
 
@@ -82,30 +218,129 @@ fn range_from(len: usize, range: impl RangeBounds<usize>) -> Range<usize> {
     Range { start, end }
 }
 
-impl<T: Clone, I: SliceIndex<[T]>, F: Fn(&T, &T) -> T> Index<I> for SegTree<T, F> {
+impl<T: Clone, I: SliceIndex<[T]>, F: Prod<T>> Index<I> for SegTree<T, F> {
     type Output = I::Output;
     fn index(&self, index: I) -> &I::Output {
         &self.data[self.len..][index]
     }
 }
 
-pub struct GetMut<'a, T: Clone, F: Fn(&T, &T) -> T> {
+pub struct GetMut<'a, T: Clone, F: Prod<T>> {
     seg: &'a mut SegTree<T, F>,
     index: usize,
 }
-impl<T: Clone, F: Fn(&T, &T) -> T> Deref for GetMut<'_, T, F> {
+impl<T: Clone, F: Prod<T>> Deref for GetMut<'_, T, F> {
     type Target = T;
     fn deref(&self) -> &T {
         &self.seg.data[self.index]
     }
 }
-impl<T: Clone, F: Fn(&T, &T) -> T> DerefMut for GetMut<'_, T, F> {
+impl<T: Clone, F: Prod<T>> DerefMut for GetMut<'_, T, F> {
     fn deref_mut(&mut self) -> &mut T {
         &mut self.seg.data[self.index]
     }
 }
-impl<T: Clone, F: Fn(&T, &T) -> T> Drop for GetMut<'_, T, F> {
+impl<T: Clone, F: Prod<T>> Drop for GetMut<'_, T, F> {
     fn drop(&mut self) {
         self.seg.update_parent(self.index)
     }
 }
+
+pub struct SetAll<'a, T: Clone, F: Prod<T>> {
+    seg: &'a mut SegTree<T, F>,
+}
+impl<T: Clone, F: Prod<T>> Deref for SetAll<'_, T, F> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        &self.seg.data[self.seg.len..]
+    }
+}
+impl<T: Clone, F: Prod<T>> DerefMut for SetAll<'_, T, F> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        let len = self.seg.len;
+        &mut self.seg.data[len..]
+    }
+}
+impl<T: Clone, F: Prod<T>> Drop for SetAll<'_, T, F> {
+    fn drop(&mut self) {
+        self.seg.rebuild()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_max_right_min_left() {
+        let mut seed = 1;
+        for len in 0..40 {
+            let vec: Vec<u64> = (0..len).map(|_| xorshift(&mut seed) % 10).collect();
+            let seg = SegTree::from_vec(vec.clone(), 0, |a: &u64, b: &u64| a + b);
+            for x in 0..50 {
+                for l in 0..=len {
+                    let expected = (l..=len)
+                        .rev()
+                        .find(|&r| vec[l..r].iter().sum::<u64>() <= x)
+                        .unwrap();
+                    assert_eq!(seg.max_right(l, |&s| s <= x), expected);
+                }
+                for r in 0..=len {
+                    let expected = (0..=r)
+                        .find(|&l| vec[l..r].iter().sum::<u64>() <= x)
+                        .unwrap();
+                    assert_eq!(seg.min_left(r, |&s| s <= x), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_right_min_left_noncommutative() {
+        let mut seed = 2;
+        let len = 23;
+        let vec: Vec<u64> = (0..len).map(|_| xorshift(&mut seed) % 3).collect();
+        let mut seg = SegTree::new(len, vec![], |a: &Vec<u64>, b: &Vec<u64>| {
+            a.iter().chain(b).copied().collect()
+        });
+        for (i, &v) in vec.iter().enumerate() {
+            *seg.get_mut(i) = vec![v];
+        }
+        // 先頭から見て昇順であるか
+        let sorted = |v: &[u64]| v.windows(2).all(|w| w[0] <= w[1]);
+        for l in 0..=len {
+            let expected = (l..=len).rev().find(|&r| sorted(&vec[l..r])).unwrap();
+            assert_eq!(seg.max_right(l, |v| sorted(v)), expected);
+        }
+        for r in 0..=len {
+            let expected = (0..=r).find(|&l| sorted(&vec[l..r])).unwrap();
+            assert_eq!(seg.min_left(r, |v| sorted(v)), expected);
+        }
+    }
+
+    #[test]
+    fn test_from_vec_set_all() {
+        let mut seed = 3;
+        for len in 0..20 {
+            let mut vec: Vec<u64> = (0..len).map(|_| xorshift(&mut seed) % 100).collect();
+            let mut seg = SegTree::from_iter(vec.iter().copied(), 0, |a: &u64, b: &u64| a + b);
+            let check = |seg: &SegTree<u64, _>, vec: &[u64]| {
+                for l in 0..=len {
+                    for r in l..=len {
+                        assert_eq!(seg.prod_range(l..r), vec[l..r].iter().sum::<u64>());
+                    }
+                }
+            };
+            check(&seg, &vec);
+            {
+                let mut leaves = seg.set_all();
+                for (leaf, v) in leaves.iter_mut().zip(&mut vec) {
+                    *v = xorshift(&mut seed) % 100;
+                    *leaf = *v;
+                }
+            }
+            check(&seg, &vec);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! `SegTree` に渡せる典型的なモノイド
+
+use std::marker::PhantomData;
+
+/// `lazysegtree::Type` と同様の形のモノイド
+pub trait Monoid {
+    type Item: Clone;
+    fn id() -> Self::Item;
+    fn prod(a: &Self::Item, b: &Self::Item) -> Self::Item;
+}
+
+/// 結合的な二項演算、`Fn(&T, &T) -> T` なクロージャと `ByMonoid` が実装する
+pub trait Prod<T> {
+    fn prod(&self, a: &T, b: &T) -> T;
+}
+
+impl<T, F: Fn(&T, &T) -> T> Prod<T> for F {
+    fn prod(&self, a: &T, b: &T) -> T {
+        self(a, b)
+    }
+}
+
+/// `Monoid` の積を `Prod` として使う
+pub struct ByMonoid<M>(PhantomData<M>);
+
+impl<M> Default for ByMonoid<M> {
+    fn default() -> Self {
+        ByMonoid(PhantomData)
+    }
+}
+
+impl<M> Clone for ByMonoid<M> {
+    fn clone(&self) -> Self {
+        ByMonoid(PhantomData)
+    }
+}
+
+impl<M: Monoid> Prod<M::Item> for ByMonoid<M> {
+    fn prod(&self, a: &M::Item, b: &M::Item) -> M::Item {
+        M::prod(a, b)
+    }
+}
+
+/// 和
+pub struct Sum<T>(PhantomData<T>);
+/// 最小値
+pub struct Min<T>(PhantomData<T>);
+/// 最大値
+pub struct Max<T>(PhantomData<T>);
+/// `(値, 添字)` の最小値、同じ値なら添字が最小のもの
+/// 単位元は `(T::max_value(), usize::max_value())`
+pub struct MinWithIndex<T>(PhantomData<T>);
+/// 最大公約数、単位元は `0`
+pub struct Gcd<T>(PhantomData<T>);
+/// 排他的論理和
+pub struct Xor<T>(PhantomData<T>);
+/// 2x2 行列の積、`prod(a, b) = a * b`
+pub struct Matrix2<T>(PhantomData<T>);
+/// `(a, b)` は `x -> a * x + b` を表す
+/// `prod(f, g)` は `f` を適用したあと `g` を適用する関数
+pub struct Affine<T>(PhantomData<T>);
+/// 最大部分列和、空の部分列を許す
+pub struct MaxSubarraySum<T>(PhantomData<T>);
+
+/// `MaxSubarraySum` の要素
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Subarray<T> {
+    pub sum: T,
+    pub prefix: T,
+    pub suffix: T,
+    pub max: T,
+}
+
+macro_rules! impl_monoid {
+    ($($t:ty),*) => {$(
+        impl Monoid for Sum<$t> {
+            type Item = $t;
+            fn id() -> $t {
+                0
+            }
+            fn prod(a: &$t, b: &$t) -> $t {
+                a + b
+            }
+        }
+        impl Monoid for Min<$t> {
+            type Item = $t;
+            fn id() -> $t {
+                <$t>::max_value()
+            }
+            fn prod(a: &$t, b: &$t) -> $t {
+                *a.min(b)
+            }
+        }
+        impl Monoid for Max<$t> {
+            type Item = $t;
+            fn id() -> $t {
+                <$t>::min_value()
+            }
+            fn prod(a: &$t, b: &$t) -> $t {
+                *a.max(b)
+            }
+        }
+        impl Monoid for MinWithIndex<$t> {
+            type Item = ($t, usize);
+            fn id() -> ($t, usize) {
+                (<$t>::max_value(), usize::max_value())
+            }
+            fn prod(a: &($t, usize), b: &($t, usize)) -> ($t, usize) {
+                *a.min(b)
+            }
+        }
+        impl Monoid for Xor<$t> {
+            type Item = $t;
+            fn id() -> $t {
+                0
+            }
+            fn prod(a: &$t, b: &$t) -> $t {
+                a ^ b
+            }
+        }
+        impl Monoid for Matrix2<$t> {
+            type Item = [[$t; 2]; 2];
+            fn id() -> [[$t; 2]; 2] {
+                [[1, 0], [0, 1]]
+            }
+            fn prod(a: &[[$t; 2]; 2], b: &[[$t; 2]; 2]) -> [[$t; 2]; 2] {
+                let mut c = [[0; 2]; 2];
+                for i in 0..2 {
+                    for j in 0..2 {
+                        c[i][j] = a[i][0] * b[0][j] + a[i][1] * b[1][j];
+                    }
+                }
+                c
+            }
+        }
+        impl Monoid for Affine<$t> {
+            type Item = ($t, $t);
+            fn id() -> ($t, $t) {
+                (1, 0)
+            }
+            fn prod(&(a, b): &($t, $t), &(c, d): &($t, $t)) -> ($t, $t) {
+                (a * c, b * c + d)
+            }
+        }
+        impl Monoid for MaxSubarraySum<$t> {
+            type Item = Subarray<$t>;
+            fn id() -> Subarray<$t> {
+                <Subarray<$t>>::new(0)
+            }
+            fn prod(a: &Subarray<$t>, b: &Subarray<$t>) -> Subarray<$t> {
+                Subarray {
+                    sum: a.sum + b.sum,
+                    prefix: a.prefix.max(a.sum + b.prefix),
+                    suffix: b.suffix.max(a.suffix + b.sum),
+                    max: a.max.max(b.max).max(a.suffix + b.prefix),
+                }
+            }
+        }
+        impl Subarray<$t> {
+            /// 長さ `1` の列 `[x]`
+            pub fn new(x: $t) -> Self {
+                let y = x.max(0);
+                Self {
+                    sum: x,
+                    prefix: y,
+                    suffix: y,
+                    max: y,
+                }
+            }
+        }
+    )*};
+}
+impl_monoid!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_gcd {
+    ($($t:ty),*) => {$(
+        impl Monoid for Gcd<$t> {
+            type Item = $t;
+            fn id() -> $t {
+                0
+            }
+            fn prod(&a: &$t, &b: &$t) -> $t {
+                let (mut a, mut b) = (a, b);
+                while b != 0 {
+                    a %= b;
+                    std::mem::swap(&mut a, &mut b);
+                }
+                a
+            }
+        }
+    )*};
+}
+impl_gcd!(u8, u16, u32, u64, u128, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MonoidSegTree;
+
+    fn check<M: Monoid>(vec: Vec<M::Item>)
+    where
+        M::Item: PartialEq + std::fmt::Debug,
+    {
+        let seg = MonoidSegTree::<M>::from_vec_monoid(vec.clone());
+        for l in 0..=vec.len() {
+            for r in l..=vec.len() {
+                let expected = vec[l..r].iter().fold(M::id(), |acc, x| M::prod(&acc, x));
+                assert_eq!(seg.prod_range(l..r), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_monoids() {
+        let vec = vec![3i64, -1, 4, -1, -5, 9, 2, -6, 5, 3];
+        check::<Sum<i64>>(vec.clone());
+        check::<Min<i64>>(vec.clone());
+        check::<Max<i64>>(vec.clone());
+        check::<Xor<i64>>(vec.clone());
+        check::<MinWithIndex<i64>>(vec.iter().copied().zip(0..).collect());
+        check::<Gcd<u64>>(vec.iter().map(|&x| x.abs() as u64 * 6).collect());
+        check::<Matrix2<i64>>(vec.iter().map(|&x| [[x, 1], [1, 0]]).collect());
+        check::<Affine<i64>>(vec.iter().map(|&x| (x, x + 1)).collect());
+        check::<MaxSubarraySum<i64>>(vec.iter().map(|&x| Subarray::<i64>::new(x)).collect());
+    }
+
+    #[test]
+    fn test_max_subarray_sum() {
+        let vec = [3i64, -1, 4, -1, -5, 9, 2, -6, 5, 3];
+        let seg = MonoidSegTree::<MaxSubarraySum<i64>>::from_iter_monoid(
+            vec.iter().map(|&x| Subarray::<i64>::new(x)),
+        );
+        for l in 0..=vec.len() {
+            for r in l..=vec.len() {
+                let mut expected = 0;
+                for i in l..r {
+                    for j in i + 1..=r {
+                        expected = expected.max(vec[i..j].iter().sum());
+                    }
+                }
+                assert_eq!(seg.prod_range(l..r).max, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_affine() {
+        let seg = MonoidSegTree::<Affine<i64>>::from_vec_monoid(vec![(2, 1), (3, -4)]);
+        // 3 * (2 * x + 1) - 4
+        assert_eq!(seg.prod_range(..), (6, -1));
+    }
+}
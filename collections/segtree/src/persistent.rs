@@ -0,0 +1,262 @@
+use crate::{range_from, ByMonoid, Monoid, Prod};
+use std::ops::{Range, RangeBounds};
+
+/// 完全永続 `SegTree`
+/// 各 `set` は新しい版を作り、過去の版はすべて読み書きできる
+pub struct PersistentSegTree<T, F> {
+    nodes: Vec<Node<T>>,
+    len: usize,
+    initial: Version,
+    id: T,
+    prod: F,
+}
+
+/// `PersistentSegTree` の版
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Version(usize);
+
+struct Node<T> {
+    val: T,
+    left: usize,
+    right: usize,
+}
+
+impl<T: Clone, F: Fn(&T, &T) -> T> PersistentSegTree<T, F> {
+    pub fn new(len: usize, id: T, prod: F) -> Self {
+        Self::from_vec_with(vec![id.clone(); len], id, prod)
+    }
+    /// O(n) で構築する
+    pub fn from_vec(vec: Vec<T>, id: T, prod: F) -> Self {
+        Self::from_vec_with(vec, id, prod)
+    }
+}
+
+/// `Monoid` によって定まる `PersistentSegTree`
+pub type MonoidPersistentSegTree<M> = PersistentSegTree<<M as Monoid>::Item, ByMonoid<M>>;
+
+impl<M: Monoid> MonoidPersistentSegTree<M> {
+    pub fn new_monoid(len: usize) -> Self {
+        Self::from_vec_monoid(vec![M::id(); len])
+    }
+    /// O(n) で構築する
+    pub fn from_vec_monoid(vec: Vec<M::Item>) -> Self {
+        Self::from_vec_with(vec, M::id(), ByMonoid::default())
+    }
+}
+
+impl<T: Clone, F: Prod<T>> PersistentSegTree<T, F> {
+    fn from_vec_with(vec: Vec<T>, id: T, prod: F) -> Self {
+        let mut seg = Self {
+            nodes: Vec::with_capacity(vec.len() * 2),
+            len: vec.len(),
+            initial: Version(0),
+            id,
+            prod,
+        };
+        if vec.is_empty() {
+            let val = seg.id.clone();
+            seg.push(val, 0, 0);
+        } else {
+            let root = seg.build(&vec);
+            seg.initial = Version(root);
+        }
+        seg
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// 構築直後の版
+    pub fn initial(&self) -> Version {
+        self.initial
+    }
+    pub fn get(&self, version: Version, i: usize) -> &T {
+        assert!(i < self.len, "index out: {}/{}", i, self.len);
+        let (mut node, mut l, mut r) = (version.0, 0, self.len);
+        while r - l > 1 {
+            let mid = (l + r) / 2;
+            if i < mid {
+                node = self.nodes[node].left;
+                r = mid;
+            } else {
+                node = self.nodes[node].right;
+                l = mid;
+            }
+        }
+        &self.nodes[node].val
+    }
+    /// `version` の `i` 番目を `val` にした新しい版を返す
+    /// O(log n)
+    pub fn set(&mut self, version: Version, i: usize, val: T) -> Version {
+        assert!(i < self.len, "index out: {}/{}", i, self.len);
+        Version(self.set_rec(version.0, 0, self.len, i, val))
+    }
+    /// O(log n)
+    pub fn prod_range(&self, version: Version, range: impl RangeBounds<usize>) -> T {
+        let Range { start, end } = range_from(self.len, range);
+        if start == end {
+            return self.id.clone();
+        }
+        self.prod_rec(version.0, 0, self.len, start, end)
+    }
+    /// `version` 全体の積
+    pub fn prod(&self, version: Version) -> &T {
+        &self.nodes[version.0].val
+    }
+    fn push(&mut self, val: T, left: usize, right: usize) -> usize {
+        self.nodes.push(Node { val, left, right });
+        self.nodes.len() - 1
+    }
+    fn build(&mut self, slice: &[T]) -> usize {
+        if slice.len() == 1 {
+            return self.push(slice[0].clone(), 0, 0);
+        }
+        let mid = slice.len() / 2;
+        let left = self.build(&slice[..mid]);
+        let right = self.build(&slice[mid..]);
+        let val = self
+            .prod
+            .prod(&self.nodes[left].val, &self.nodes[right].val);
+        self.push(val, left, right)
+    }
+    fn set_rec(&mut self, node: usize, l: usize, r: usize, i: usize, val: T) -> usize {
+        if r - l == 1 {
+            return self.push(val, 0, 0);
+        }
+        let mid = (l + r) / 2;
+        let (mut left, mut right) = (self.nodes[node].left, self.nodes[node].right);
+        if i < mid {
+            left = self.set_rec(left, l, mid, i, val);
+        } else {
+            right = self.set_rec(right, mid, r, i, val);
+        }
+        let val = self
+            .prod
+            .prod(&self.nodes[left].val, &self.nodes[right].val);
+        self.push(val, left, right)
+    }
+    fn prod_rec(&self, node: usize, l: usize, r: usize, start: usize, end: usize) -> T {
+        if (start, end) == (l, r) {
+            return self.nodes[node].val.clone();
+        }
+        let mid = (l + r) / 2;
+        let Node { left, right, .. } = self.nodes[node];
+        if end <= mid {
+            self.prod_rec(left, l, mid, start, end)
+        } else if mid <= start {
+            self.prod_rec(right, mid, r, start, end)
+        } else {
+            self.prod.prod(
+                &self.prod_rec(left, l, mid, start, mid),
+                &self.prod_rec(right, mid, r, mid, end),
+            )
+        }
+    }
+}
+
+impl<F: Prod<usize>> PersistentSegTree<usize, F> {
+    /// 個数を和で管理しているとき、`version` における `k` 番目 (0-indexed) の要素の添字
+    /// 要素数が `k` 以下なら `None`
+    pub fn kth(&self, version: Version, k: usize) -> Option<usize> {
+        self.kth_diff(version, None, k)
+    }
+    /// 個数を和で管理しているとき、`hi` から `lo` を引いた多重集合における
+    /// `k` 番目 (0-indexed) の要素の添字
+    /// `lo` は `hi` の過去の版であるなど、各点で `lo` の個数が `hi` 以下である必要がある
+    pub fn kth_diff(&self, hi: Version, lo: Option<Version>, mut k: usize) -> Option<usize> {
+        let count = |hi: usize, lo: Option<usize>| {
+            self.nodes[hi].val - lo.map_or(0, |lo| self.nodes[lo].val)
+        };
+        let (mut hi, mut lo) = (hi.0, lo.map(|lo| lo.0));
+        if self.is_empty() || count(hi, lo) <= k {
+            return None;
+        }
+        let (mut l, mut r) = (0, self.len);
+        while r - l > 1 {
+            let mid = (l + r) / 2;
+            let (hi_left, lo_left) = (self.nodes[hi].left, lo.map(|lo| self.nodes[lo].left));
+            let c = count(hi_left, lo_left);
+            if k < c {
+                hi = hi_left;
+                lo = lo_left;
+                r = mid;
+            } else {
+                k -= c;
+                hi = self.nodes[hi].right;
+                lo = lo.map(|lo| self.nodes[lo].right);
+                l = mid;
+            }
+        }
+        Some(l)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monoid::Sum;
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_versions() {
+        let mut seed = 5;
+        for len in 1..12 {
+            let mut seg = MonoidPersistentSegTree::<Sum<u64>>::new_monoid(len);
+            let mut versions = vec![(seg.initial(), vec![0; len])];
+            for _ in 0..30 {
+                let k = xorshift(&mut seed) as usize % versions.len();
+                let i = xorshift(&mut seed) as usize % len;
+                let v = xorshift(&mut seed) % 100;
+                let (version, mut vec) = versions[k].clone();
+                vec[i] = v;
+                versions.push((seg.set(version, i, v), vec));
+            }
+            for (version, vec) in &versions {
+                for l in 0..=len {
+                    for r in l..=len {
+                        assert_eq!(seg.prod_range(*version, l..r), vec[l..r].iter().sum());
+                    }
+                }
+                for (i, v) in vec.iter().enumerate() {
+                    assert_eq!(seg.get(*version, i), v);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_kth_in_subarray() {
+        let mut seed = 6;
+        let vec: Vec<usize> = (0..30).map(|_| xorshift(&mut seed) as usize % 10).collect();
+        let mut seg = MonoidPersistentSegTree::<Sum<usize>>::new_monoid(10);
+        // prefix[i]: vec[..i] に含まれる値の個数
+        let mut prefix = vec![seg.initial()];
+        for &x in &vec {
+            let last = *prefix.last().unwrap();
+            let count = *seg.get(last, x);
+            prefix.push(seg.set(last, x, count + 1));
+        }
+        for l in 0..=vec.len() {
+            for r in l..=vec.len() {
+                let mut sorted = vec[l..r].to_vec();
+                sorted.sort();
+                for k in 0..=sorted.len() {
+                    assert_eq!(
+                        seg.kth_diff(prefix[r], Some(prefix[l]), k),
+                        sorted.get(k).copied()
+                    );
+                }
+            }
+        }
+        assert_eq!(seg.kth(prefix[3], 0), vec[..3].iter().min().copied());
+    }
+
+    #[test]
+    fn test_empty() {
+        let seg = PersistentSegTree::new(0, 0, |a: &i32, b: &i32| a + b);
+        assert_eq!(seg.prod_range(seg.initial(), ..), 0);
+        assert_eq!(*seg.prod(seg.initial()), 0);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{range_from, ByMonoid, Monoid, Prod};
+use std::ops::{Deref, DerefMut, Range, RangeBounds};
+
+/// `H` × `W` の格子上の `SegTree`
+/// `prod` は可換である必要がある
+pub struct SegTree2D<T, F> {
+    data: Vec<T>,
+    height: usize,
+    width: usize,
+    id: T,
+    prod: F,
+}
+
+impl<T: Clone, F: Fn(&T, &T) -> T> SegTree2D<T, F> {
+    pub fn new(height: usize, width: usize, id: T, prod: F) -> Self {
+        Self::new_with(height, width, id, prod)
+    }
+    /// O(HW) で構築する
+    pub fn from_vec(vec: Vec<Vec<T>>, id: T, prod: F) -> Self {
+        Self::from_vec_with(vec, id, prod)
+    }
+}
+
+/// `Monoid` によって定まる `SegTree2D`
+pub type MonoidSegTree2D<M> = SegTree2D<<M as Monoid>::Item, ByMonoid<M>>;
+
+impl<M: Monoid> MonoidSegTree2D<M> {
+    pub fn new_monoid(height: usize, width: usize) -> Self {
+        Self::new_with(height, width, M::id(), ByMonoid::default())
+    }
+    /// O(HW) で構築する
+    pub fn from_vec_monoid(vec: Vec<Vec<M::Item>>) -> Self {
+        Self::from_vec_with(vec, M::id(), ByMonoid::default())
+    }
+}
+
+impl<T: Clone, F: Prod<T>> SegTree2D<T, F> {
+    fn new_with(height: usize, width: usize, id: T, prod: F) -> Self {
+        Self {
+            data: vec![id.clone(); height * 2 * width * 2],
+            height,
+            width,
+            id,
+            prod,
+        }
+    }
+    fn from_vec_with(vec: Vec<Vec<T>>, id: T, prod: F) -> Self {
+        let height = vec.len();
+        let width = vec.first().map_or(0, Vec::len);
+        let mut seg = Self::new_with(height, width, id, prod);
+        for (i, row) in vec.into_iter().enumerate() {
+            assert_eq!(row.len(), width, "not a rectangle");
+            for (j, val) in row.into_iter().enumerate() {
+                let k = seg.pos(height + i, width + j);
+                seg.data[k] = val;
+            }
+        }
+        for i in height..height * 2 {
+            for j in (1..width).rev() {
+                seg.update_col(i, j);
+            }
+        }
+        for i in (1..height).rev() {
+            for j in 1..width * 2 {
+                seg.update_row(i, j);
+            }
+        }
+        seg
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn get(&self, i: usize, j: usize) -> &T {
+        assert!(i < self.height, "index out: {}/{}", i, self.height);
+        assert!(j < self.width, "index out: {}/{}", j, self.width);
+        &self.data[self.pos(self.height + i, self.width + j)]
+    }
+    pub fn get_mut(&mut self, i: usize, j: usize) -> GetMut2D<T, F> {
+        assert!(i < self.height, "index out: {}/{}", i, self.height);
+        assert!(j < self.width, "index out: {}/{}", j, self.width);
+        GetMut2D {
+            i: self.height + i,
+            j: self.width + j,
+            seg: self,
+        }
+    }
+    /// O(log H log W)
+    pub fn prod_rect(&self, rows: impl RangeBounds<usize>, cols: impl RangeBounds<usize>) -> T {
+        let Range { mut start, mut end } = range_from(self.height, rows);
+        let cols = range_from(self.width, cols);
+        start += self.height;
+        end += self.height;
+        let mut ret = self.id.clone();
+        while start != end {
+            if start & 1 != 0 {
+                ret = self.prod.prod(&ret, &self.prod_row(start, cols.clone()));
+                start += 1;
+            }
+            if end & 1 != 0 {
+                end -= 1;
+                ret = self.prod.prod(&ret, &self.prod_row(end, cols.clone()));
+            }
+            start >>= 1;
+            end >>= 1;
+        }
+        ret
+    }
+    fn prod_row(&self, i: usize, Range { mut start, mut end }: Range<usize>) -> T {
+        start += self.width;
+        end += self.width;
+        let mut ret = self.id.clone();
+        while start != end {
+            if start & 1 != 0 {
+                ret = self.prod.prod(&ret, &self.data[self.pos(i, start)]);
+                start += 1;
+            }
+            if end & 1 != 0 {
+                end -= 1;
+                ret = self.prod.prod(&ret, &self.data[self.pos(i, end)]);
+            }
+            start >>= 1;
+            end >>= 1;
+        }
+        ret
+    }
+    fn pos(&self, i: usize, j: usize) -> usize {
+        i * self.width * 2 + j
+    }
+    /// 行 `i` の節点 `j` を子から計算する
+    fn update_col(&mut self, i: usize, j: usize) {
+        let val = self.prod.prod(
+            &self.data[self.pos(i, 2 * j)],
+            &self.data[self.pos(i, 2 * j + 1)],
+        );
+        let k = self.pos(i, j);
+        self.data[k] = val;
+    }
+    /// 節点 `i` の列 `j` を子から計算する
+    fn update_row(&mut self, i: usize, j: usize) {
+        let val = self.prod.prod(
+            &self.data[self.pos(2 * i, j)],
+            &self.data[self.pos(2 * i + 1, j)],
+        );
+        let k = self.pos(i, j);
+        self.data[k] = val;
+    }
+    fn update_parent(&mut self, i: usize, j: usize) {
+        let mut jj = j >> 1;
+        while jj != 0 {
+            self.update_col(i, jj);
+            jj >>= 1;
+        }
+        let mut ii = i >> 1;
+        while ii != 0 {
+            let mut jj = j;
+            while jj != 0 {
+                self.update_row(ii, jj);
+                jj >>= 1;
+            }
+            ii >>= 1;
+        }
+    }
+}
+
+pub struct GetMut2D<'a, T: Clone, F: Prod<T>> {
+    seg: &'a mut SegTree2D<T, F>,
+    i: usize,
+    j: usize,
+}
+impl<T: Clone, F: Prod<T>> Deref for GetMut2D<'_, T, F> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.seg.data[self.seg.pos(self.i, self.j)]
+    }
+}
+impl<T: Clone, F: Prod<T>> DerefMut for GetMut2D<'_, T, F> {
+    fn deref_mut(&mut self) -> &mut T {
+        let k = self.seg.pos(self.i, self.j);
+        &mut self.seg.data[k]
+    }
+}
+impl<T: Clone, F: Prod<T>> Drop for GetMut2D<'_, T, F> {
+    fn drop(&mut self) {
+        self.seg.update_parent(self.i, self.j)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monoid::{Min, Sum};
+    use crate::test_util::xorshift;
+
+    #[test]
+    fn test_prod_rect() {
+        let mut seed = 4;
+        for &(h, w) in &[(1, 1), (3, 5), (4, 4), (7, 2), (2, 0)] {
+            let mut grid = vec![vec![0u64; w]; h];
+            let mut sum = MonoidSegTree2D::<Sum<u64>>::new_monoid(h, w);
+            let mut min = MonoidSegTree2D::<Min<u64>>::from_vec_monoid(grid.clone());
+            for _ in 0..30 {
+                if h * w != 0 {
+                    let (i, j) = (
+                        xorshift(&mut seed) as usize % h,
+                        xorshift(&mut seed) as usize % w,
+                    );
+                    let v = xorshift(&mut seed) % 100;
+                    grid[i][j] = v;
+                    *sum.get_mut(i, j) = v;
+                    *min.get_mut(i, j) = v;
+                }
+                for i0 in 0..=h {
+                    for i1 in i0..=h {
+                        for j0 in 0..=w {
+                            for j1 in j0..=w {
+                                let cells = grid[i0..i1].iter().flat_map(|row| &row[j0..j1]);
+                                assert_eq!(sum.prod_rect(i0..i1, j0..j1), cells.clone().sum());
+                                assert_eq!(
+                                    min.prod_rect(i0..i1, j0..j1),
+                                    cells.copied().min().unwrap_or(u64::max_value())
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let grid = vec![vec![3, 1, 4], vec![1, 5, 9], vec![2, 6, 5], vec![3, 5, 8]];
+        let seg = MonoidSegTree2D::<Sum<i32>>::from_vec_monoid(grid);
+        assert_eq!(seg.prod_rect(.., ..), 52);
+        assert_eq!(seg.prod_rect(1..3, 1..), 25);
+        assert_eq!(seg.prod_rect(..=0, 2..), 4);
+        assert_eq!(*seg.get(3, 2), 8);
+    }
+}
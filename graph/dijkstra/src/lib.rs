@@ -8,46 +8,93 @@ use {
     },
 };
 
-pub fn dijkstra<N, C, A, I>(start: N, goal: Option<N>, mut adj: A) -> HashMap<N, C>
+pub fn dijkstra<N, C, A, I>(start: N, goal: Option<N>, adj: A) -> HashMap<N, C>
 where
     N: Eq + Hash + Clone,
     C: Zero + Ord + Add<Output = C> + Clone,
     A: FnMut(N) -> I,
     I: IntoIterator<Item = (N, C)>,
+{
+    dijkstra_multi(
+        std::iter::once((start, C::zero())),
+        |v| goal.as_ref() == Some(v),
+        adj,
+    )
+}
+
+/// 多始点、`starts` は `(始点, 初期距離)` を列挙する
+/// `is_goal` を満たす頂点の距離が確定したら打ち切る
+pub fn dijkstra_multi<N, C, S, G, A, I>(starts: S, is_goal: G, adj: A) -> HashMap<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Ord + Add<Output = C> + Clone,
+    S: IntoIterator<Item = (N, C)>,
+    G: FnMut(&N) -> bool,
+    A: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, C)>,
 {
     let mut dist = HashMap::new();
-    let mut heap = BinaryHeap::new();
+    search(&mut dist, starts, is_goal, adj);
+    dist
+}
 
-    dist.insert(start.clone(), C::zero());
-    heap.push(KeyValue(C::zero(), start));
+/// `dijkstra` に加えて、最短路における直前の頂点を記録する
+pub fn dijkstra_with_prev<N, C, A, I>(start: N, goal: Option<N>, adj: A) -> ShortestPaths<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Zero + Ord + Add<Output = C> + Clone,
+    A: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    dijkstra_multi_with_prev(
+        std::iter::once((start, C::zero())),
+        |v| goal.as_ref() == Some(v),
+        adj,
+    )
+}
 
-    while let Some(KeyValue(dist_v, v)) = heap.pop() {
-        if dist[&v] != dist_v {
-            continue;
-        }
-        if goal.as_ref().map_or(false, |goal| &v == goal) {
-            break;
+/// `dijkstra_with_prev` などの結果
+pub struct ShortestPaths<N, C> {
+    pub dist: HashMap<N, C>,
+    /// 始点以外の各頂点について、最短路における直前の頂点
+    pub prev: HashMap<N, N>,
+}
+
+impl<N: Eq + Hash + Clone, C> ShortestPaths<N, C> {
+    /// いずれかの始点から `goal` への最短路の頂点列、到達できなければ `None`
+    pub fn path_to(&self, goal: &N) -> Option<Vec<N>> {
+        if !self.dist.contains_key(goal) {
+            return None;
         }
-        for (u, c) in adj(v) {
-            use std::collections::hash_map::Entry;
-            let dist_u_new = dist_v.clone() + c;
-            match dist.entry(u.clone()) {
-                Entry::Occupied(mut entry) => {
-                    let dist_u = entry.get_mut();
-                    if *dist_u > dist_u_new {
-                        *dist_u = dist_u_new;
-                        heap.push(KeyValue(dist_u.clone(), u));
-                    }
-                }
-                Entry::Vacant(entry) => {
-                    entry.insert(dist_u_new.clone());
-                    heap.push(KeyValue(dist_u_new, u));
-                }
-            }
+        let mut path = vec![goal.clone()];
+        while let Some(v) = self.prev.get(path.last().unwrap()) {
+            path.push(v.clone());
         }
+        path.reverse();
+        Some(path)
     }
+}
 
-    dist
+/// `dijkstra_multi` に加えて、最短路における直前の頂点を記録する
+pub fn dijkstra_multi_with_prev<N, C, S, G, A, I>(
+    starts: S,
+    is_goal: G,
+    adj: A,
+) -> ShortestPaths<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Ord + Add<Output = C> + Clone,
+    S: IntoIterator<Item = (N, C)>,
+    G: FnMut(&N) -> bool,
+    A: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut paths = ShortestPaths {
+        dist: HashMap::new(),
+        prev: HashMap::new(),
+    };
+    search(&mut paths, starts, is_goal, adj);
+    paths
 }
 
 pub fn dijkstra_usize<C, A, I>(
@@ -64,12 +111,61 @@ where
     dijkstra_vec(start, goal, n, Clone::clone, adj)
 }
 
+/// 多始点版の `dijkstra_usize`
+pub fn dijkstra_usize_multi<C, S, G, A, I>(
+    starts: S,
+    is_goal: G,
+    n: usize,
+    adj: A,
+) -> Vec<Option<C>>
+where
+    C: Ord + Clone + Add<Output = C>,
+    S: IntoIterator<Item = (usize, C)>,
+    G: FnMut(&usize) -> bool,
+    A: FnMut(usize) -> I,
+    I: IntoIterator<Item = (usize, C)>,
+{
+    dijkstra_vec_multi(starts, is_goal, n, Clone::clone, adj)
+}
+
+/// `dijkstra_usize` に加えて、最短路における直前の頂点を記録する
+pub fn dijkstra_usize_with_prev<C, A, I>(
+    start: usize,
+    goal: Option<usize>,
+    n: usize,
+    adj: A,
+) -> ShortestPathsVec<usize, C, fn(&usize) -> usize>
+where
+    C: Ord + Zero + Clone + Add<Output = C>,
+    A: FnMut(usize) -> I,
+    I: IntoIterator<Item = (usize, C)>,
+{
+    dijkstra_vec_with_prev(start, goal, n, Clone::clone, adj)
+}
+
+/// `dijkstra_usize_multi` に加えて、最短路における直前の頂点を記録する
+pub fn dijkstra_usize_multi_with_prev<C, S, G, A, I>(
+    starts: S,
+    is_goal: G,
+    n: usize,
+    adj: A,
+) -> ShortestPathsVec<usize, C, fn(&usize) -> usize>
+where
+    C: Ord + Clone + Add<Output = C>,
+    S: IntoIterator<Item = (usize, C)>,
+    G: FnMut(&usize) -> bool,
+    A: FnMut(usize) -> I,
+    I: IntoIterator<Item = (usize, C)>,
+{
+    dijkstra_vec_multi_with_prev(starts, is_goal, n, Clone::clone, adj)
+}
+
 pub fn dijkstra_vec<N, C, A, I, Id>(
     start: N,
     goal: Option<N>,
     n: usize,
     id: Id,
-    mut adj: A,
+    adj: A,
 ) -> Vec<Option<C>>
 where
     Id: Fn(&N) -> usize,
@@ -78,37 +174,241 @@ where
     A: FnMut(N) -> I,
     I: IntoIterator<Item = (N, C)>,
 {
-    let mut dist = vec![None; n];
+    dijkstra_vec_multi(
+        std::iter::once((start, C::zero())),
+        |v| goal.as_ref() == Some(v),
+        n,
+        id,
+        adj,
+    )
+}
+
+/// 多始点版の `dijkstra_vec`、`starts` と `is_goal` は `dijkstra_multi` と同様
+pub fn dijkstra_vec_multi<N, C, S, G, A, I, Id>(
+    starts: S,
+    is_goal: G,
+    n: usize,
+    id: Id,
+    adj: A,
+) -> Vec<Option<C>>
+where
+    Id: Fn(&N) -> usize,
+    C: Ord + Clone + Add<Output = C>,
+    S: IntoIterator<Item = (N, C)>,
+    G: FnMut(&N) -> bool,
+    A: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut dist = DistVec {
+        dist: vec![None; n],
+        id,
+    };
+    search(&mut dist, starts, is_goal, adj);
+    dist.dist
+}
+
+/// `dijkstra_vec` に加えて、最短路における直前の頂点を記録する
+pub fn dijkstra_vec_with_prev<N, C, A, I, Id>(
+    start: N,
+    goal: Option<N>,
+    n: usize,
+    id: Id,
+    adj: A,
+) -> ShortestPathsVec<N, C, Id>
+where
+    Id: Fn(&N) -> usize,
+    C: Ord + Zero + Clone + Add<Output = C>,
+    N: PartialEq + Clone,
+    A: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    dijkstra_vec_multi_with_prev(
+        std::iter::once((start, C::zero())),
+        |v| goal.as_ref() == Some(v),
+        n,
+        id,
+        adj,
+    )
+}
+
+/// `dijkstra_vec_with_prev` などの結果
+pub struct ShortestPathsVec<N, C, Id> {
+    pub dist: Vec<Option<C>>,
+    /// 始点以外の各頂点について、最短路における直前の頂点
+    pub prev: Vec<Option<N>>,
+    id: Id,
+}
+
+impl<N: Clone, C, Id: Fn(&N) -> usize> ShortestPathsVec<N, C, Id> {
+    /// いずれかの始点から `goal` への最短路の頂点列、到達できなければ `None`
+    pub fn path_to(&self, goal: &N) -> Option<Vec<N>> {
+        self.dist[(self.id)(goal)].as_ref()?;
+        let mut path = vec![goal.clone()];
+        while let Some(v) = &self.prev[(self.id)(path.last().unwrap())] {
+            path.push(v.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// `dijkstra_vec_multi` に加えて、最短路における直前の頂点を記録する
+pub fn dijkstra_vec_multi_with_prev<N, C, S, G, A, I, Id>(
+    starts: S,
+    is_goal: G,
+    n: usize,
+    id: Id,
+    adj: A,
+) -> ShortestPathsVec<N, C, Id>
+where
+    Id: Fn(&N) -> usize,
+    C: Ord + Clone + Add<Output = C>,
+    N: Clone,
+    S: IntoIterator<Item = (N, C)>,
+    G: FnMut(&N) -> bool,
+    A: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut paths = ShortestPathsVec {
+        dist: vec![None; n],
+        prev: vec![None; n],
+        id,
+    };
+    search(&mut paths, starts, is_goal, adj);
+    paths
+}
+
+/// 距離 (と直前の頂点) の記録先
+trait Record<N, C> {
+    /// 直前の頂点として記録するもの、記録しないなら `()`
+    type Prev;
+    fn dist(&self, v: &N) -> Option<&C>;
+    fn prev(v: &N) -> Self::Prev;
+    /// `v` の距離が `dist` より大きければ更新して `true` を返す
+    /// `prev` が `None` なら始点
+    fn relax(&mut self, v: &N, dist: &C, prev: Option<&Self::Prev>) -> bool;
+}
+
+impl<N: Eq + Hash + Clone, C: Ord + Clone> Record<N, C> for HashMap<N, C> {
+    type Prev = ();
+    fn dist(&self, v: &N) -> Option<&C> {
+        self.get(v)
+    }
+    fn prev(_: &N) {}
+    fn relax(&mut self, v: &N, dist: &C, _: Option<&()>) -> bool {
+        use std::collections::hash_map::Entry;
+        match self.entry(v.clone()) {
+            Entry::Occupied(mut entry) => {
+                let updated = entry.get() > dist;
+                if updated {
+                    entry.insert(dist.clone());
+                }
+                updated
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(dist.clone());
+                true
+            }
+        }
+    }
+}
+
+impl<N: Eq + Hash + Clone, C: Ord + Clone> Record<N, C> for ShortestPaths<N, C> {
+    type Prev = N;
+    fn dist(&self, v: &N) -> Option<&C> {
+        self.dist.get(v)
+    }
+    fn prev(v: &N) -> N {
+        v.clone()
+    }
+    fn relax(&mut self, v: &N, dist: &C, prev: Option<&N>) -> bool {
+        let updated = self.dist.relax(v, dist, None);
+        if updated {
+            match prev {
+                Some(prev) => self.prev.insert(v.clone(), prev.clone()),
+                None => self.prev.remove(v),
+            };
+        }
+        updated
+    }
+}
+
+/// `dijkstra_vec_multi` の記録先
+struct DistVec<C, Id> {
+    dist: Vec<Option<C>>,
+    id: Id,
+}
+
+fn relax_vec<C: Ord + Clone>(dist: &mut Option<C>, new: &C) -> bool {
+    let updated = dist.as_ref().map_or(true, |dist| dist > new);
+    if updated {
+        *dist = Some(new.clone());
+    }
+    updated
+}
+
+impl<N, C: Ord + Clone, Id: Fn(&N) -> usize> Record<N, C> for DistVec<C, Id> {
+    type Prev = ();
+    fn dist(&self, v: &N) -> Option<&C> {
+        self.dist[(self.id)(v)].as_ref()
+    }
+    fn prev(_: &N) {}
+    fn relax(&mut self, v: &N, dist: &C, _: Option<&()>) -> bool {
+        relax_vec(&mut self.dist[(self.id)(v)], dist)
+    }
+}
+
+impl<N: Clone, C: Ord + Clone, Id: Fn(&N) -> usize> Record<N, C> for ShortestPathsVec<N, C, Id> {
+    type Prev = N;
+    fn dist(&self, v: &N) -> Option<&C> {
+        self.dist[(self.id)(v)].as_ref()
+    }
+    fn prev(v: &N) -> N {
+        v.clone()
+    }
+    fn relax(&mut self, v: &N, dist: &C, prev: Option<&N>) -> bool {
+        let i = (self.id)(v);
+        let updated = relax_vec(&mut self.dist[i], dist);
+        if updated {
+            self.prev[i] = prev.cloned();
+        }
+        updated
+    }
+}
+
+/// すべての `dijkstra*` の本体
+fn search<N, C, R, S, G, A, I>(record: &mut R, starts: S, mut is_goal: G, mut adj: A)
+where
+    C: Ord + Add<Output = C> + Clone,
+    R: Record<N, C>,
+    S: IntoIterator<Item = (N, C)>,
+    G: FnMut(&N) -> bool,
+    A: FnMut(N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
     let mut heap = BinaryHeap::new();
 
-    dist[id(&start)] = Some(C::zero());
-    heap.push(KeyValue(C::zero(), start));
+    for (start, c) in starts {
+        if record.relax(&start, &c, None) {
+            heap.push(KeyValue(c, start));
+        }
+    }
 
     while let Some(KeyValue(dist_v, v)) = heap.pop() {
-        if dist[id(&v)].as_ref().unwrap() != &dist_v {
+        if record.dist(&v) != Some(&dist_v) {
             continue;
         }
-        if goal.as_ref().map_or(false, |goal| &v == goal) {
+        if is_goal(&v) {
             break;
         }
+        let prev = R::prev(&v);
         for (u, c) in adj(v) {
             let dist_u_new = dist_v.clone() + c;
-            match dist[id(&u)].as_mut() {
-                Some(dist_u) => {
-                    if *dist_u > dist_u_new {
-                        *dist_u = dist_u_new;
-                        heap.push(KeyValue(dist_u.clone(), u));
-                    }
-                }
-                None => {
-                    dist[id(&u)] = Some(dist_u_new.clone());
-                    heap.push(KeyValue(dist_u_new, u));
-                }
+            if record.relax(&u, &dist_u_new, Some(&prev)) {
+                heap.push(KeyValue(dist_u_new, u));
             }
         }
     }
-
-    dist
 }
 
 pub trait Zero {
@@ -150,3 +450,171 @@ impl<K: Ord, V> Ord for KeyValue<K, V> {
         rhs.0.cmp(&self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0 -> 1 -> 3 -> 4 が最短、2 と 5 へは遠回り、6 へは到達できない
+    fn adj(v: usize) -> Vec<(usize, u32)> {
+        match v {
+            0 => vec![(1, 2), (2, 5), (3, 9)],
+            1 => vec![(3, 3), (2, 4)],
+            2 => vec![(5, 1)],
+            3 => vec![(4, 1), (5, 10)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_dist() {
+        let dist = dijkstra(0, None, adj);
+        assert_eq!(dist[&4], 6);
+        assert_eq!(dist[&5], 6);
+        assert!(!dist.contains_key(&6));
+        assert_eq!(
+            dijkstra_usize(0, None, 7, adj),
+            vec![Some(0), Some(2), Some(5), Some(5), Some(6), Some(6), None]
+        );
+
+        // `dijkstra_vec` の頂点は `Clone` でなくてよい
+        #[derive(PartialEq)]
+        struct V(usize);
+        let dist = dijkstra_vec(
+            V(0),
+            Some(V(4)),
+            7,
+            |v: &V| v.0,
+            |V(v)| adj(v).into_iter().map(|(u, c)| (V(u), c)),
+        );
+        assert_eq!(dist[4], Some(6));
+    }
+
+    #[test]
+    fn test_path() {
+        let paths = dijkstra_with_prev(0, None, adj);
+        assert_eq!(paths.path_to(&4), Some(vec![0, 1, 3, 4]));
+        assert_eq!(paths.path_to(&5), Some(vec![0, 2, 5]));
+        assert_eq!(paths.path_to(&0), Some(vec![0]));
+        assert_eq!(paths.path_to(&6), None);
+
+        let paths = dijkstra_usize_with_prev(0, Some(4), 7, adj);
+        assert_eq!(paths.dist[4], Some(6));
+        assert_eq!(paths.path_to(&4), Some(vec![0, 1, 3, 4]));
+        assert_eq!(paths.path_to(&6), None);
+
+        // 頂点が (行, 列) のグリッド
+        let grid = ["..#", "#..", "##."];
+        let paths = dijkstra_vec_with_prev(
+            (0, 0),
+            None,
+            9,
+            |&(i, j): &(usize, usize)| i * 3 + j,
+            |(i, j)| {
+                let mut next = vec![];
+                if i + 1 < 3 {
+                    next.push((i + 1, j));
+                }
+                if j + 1 < 3 {
+                    next.push((i, j + 1));
+                }
+                next.into_iter()
+                    .filter(|&(i, j)| grid[i].as_bytes()[j] == b'.')
+                    .map(|v| (v, 1u32))
+                    .collect::<Vec<_>>()
+            },
+        );
+        assert_eq!(paths.dist[8], Some(4));
+        assert_eq!(
+            paths.path_to(&(2, 2)),
+            Some(vec![(0, 0), (0, 1), (1, 1), (1, 2), (2, 2)])
+        );
+    }
+
+    #[test]
+    fn test_multi() {
+        // 0 と 4 から出発、4 は初期距離 3
+        let dist = dijkstra_multi(
+            vec![(0, 0), (4, 3), (4, 5)],
+            |_| false,
+            |v: usize| {
+                let mut next = vec![];
+                if v > 0 {
+                    next.push((v - 1, 1u32));
+                }
+                if v < 6 {
+                    next.push((v + 1, 1));
+                }
+                next
+            },
+        );
+        let mut dist: Vec<_> = dist.into_iter().collect();
+        dist.sort();
+        assert_eq!(
+            dist,
+            vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 3), (5, 4), (6, 5)]
+        );
+
+        // 最も近い出口 (#) を探す、(0, 4) へは 2、(2, 0) へは 4
+        let grid = ["....#", ".....", "#...."];
+        let mut exit = None;
+        let dist = dijkstra_vec_multi(
+            vec![((1, 3), 0u32)],
+            |&(i, j): &(usize, usize)| {
+                let found = grid[i].as_bytes()[j] == b'#';
+                if found {
+                    exit = Some((i, j));
+                }
+                found
+            },
+            15,
+            |&(i, j)| i * 5 + j,
+            |(i, j)| {
+                let mut next = vec![];
+                if i > 0 {
+                    next.push(((i - 1, j), 1));
+                }
+                if i < 2 {
+                    next.push(((i + 1, j), 1));
+                }
+                if j > 0 {
+                    next.push(((i, j - 1), 1));
+                }
+                if j < 4 {
+                    next.push(((i, j + 1), 1));
+                }
+                next
+            },
+        );
+        assert_eq!(exit, Some((0, 4)));
+        assert_eq!(dist[4], Some(2));
+
+        assert_eq!(
+            dijkstra_usize_multi(vec![(2, 10u32), (1, 0)], |&v| v == 3, 7, adj)[3],
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_multi_path() {
+        // 0 と 5 から出発し、3 に着いたら打ち切る
+        let line = |v: usize| {
+            let mut next = vec![];
+            if v > 0 {
+                next.push((v - 1, 1u32));
+            }
+            if v < 6 {
+                next.push((v + 1, 1));
+            }
+            next
+        };
+        let paths = dijkstra_usize_multi_with_prev(vec![(0, 0), (5, 0)], |&v| v == 3, 7, line);
+        assert_eq!(paths.dist[3], Some(2));
+        assert_eq!(paths.path_to(&3), Some(vec![5, 4, 3]));
+        assert_eq!(paths.path_to(&1), Some(vec![0, 1]));
+
+        let paths = dijkstra_multi_with_prev(vec![(0, 4), (5, 0)], |_| false, line);
+        assert_eq!(paths.path_to(&2), Some(vec![5, 4, 3, 2]));
+        assert_eq!(paths.path_to(&0), Some(vec![0]));
+    }
+}